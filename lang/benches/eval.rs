@@ -0,0 +1,67 @@
+#![feature(test)]
+
+extern crate test;
+
+use test::Bencher;
+
+// cargo bench, median of three runs, against the per-call HashMap context the frames replaced
+// each iteration parses the code as well, so these are a little under what eval alone gained
+//   recursive_fib  17.3ms -> 1.21ms  14x
+//   tight_loop     14.3ms -> 1.36ms  10.5x
+//   global_loop    10.5ms -> 1.08ms  9.7x
+
+fn run(code: &str) {
+    let context = modu::utils::create_context();
+    modu::parser::parse(code, "<bench>", &context);
+}
+
+#[bench]
+fn recursive_fib(b: &mut Bencher) {
+    let code = "
+fn fib(n) {
+    if n < 2 {
+        return n;
+    }
+
+    return fib(n - 1) + fib(n - 2);
+}
+
+let result = fib(15);
+";
+
+    b.iter(|| run(code));
+}
+
+#[bench]
+fn tight_loop(b: &mut Bencher) {
+    let code = "
+fn count(n) {
+    let total = 0;
+    let i = 0;
+
+    while i < n {
+        total += i % 7;
+        i += 1;
+    }
+
+    return total;
+}
+
+let result = count(10000);
+";
+
+    b.iter(|| run(code));
+}
+
+#[bench]
+fn global_loop(b: &mut Bencher) {
+    let code = "
+let total = 0;
+
+for i = 0..10000 {
+    total += i % 7;
+}
+";
+
+    b.iter(|| run(code));
+}
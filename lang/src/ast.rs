@@ -1,47 +1,73 @@
-use std::collections::HashMap;
-#[cfg(not(target_arch = "wasm32"))]
-use libloading::Library;
-use std::sync::Arc;
+use std::rc::Rc;
 
+use crate::env::Slot;
+use crate::eval::BinaryOp;
 use crate::lexer::Span;
+use crate::value::Value;
 
 pub type SpannedExpr = Spanned<Expr>;
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Spanned<T> {
     pub node: T,
     pub span: Span,
 }
 
-#[derive(Debug, Clone)]
-pub struct InternalFunctionResponse {
-    pub return_value: Expr,
-    pub replace_self: Option<Expr>,
-}
-
 #[derive(Debug, Clone)]
 pub enum Expr {
     Int(i64),
     Float(f64),
     String(String),
     Identifier(String),
+    // an identifier the resolver found in an enclosing function or block, globals stay Identifier
+    Local {
+        slot: Slot,
+    },
     Bool(bool),
     Return(Box<Spanned<Expr>>),
     Null,
-    Break,
-    Continue,
+    Break(Option<String>), // break 'label;
+    Continue(Option<String>),
 
     Neg(Box<Spanned<Expr>>),
     Add(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
     Sub(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    Mul(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    Div(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    Mod(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
 
+    // slot is None when the let sets a global, let x: int = 1; has an annotation
     Let {
         name: String,
         value: Box<Spanned<Expr>>,
+        slot: Option<Slot>,
+        annotation: Option<Spanned<Type>>,
+    },
+
+    // let [a, b, ...rest] = value; or let { name, age: years } = value;
+    // a plain let x = value; stays a Let
+    Destructure {
+        pattern: Spanned<Pattern>,
+        value: Box<Spanned<Expr>>,
+    },
+
+    // x = value; arr[0] = value; obj.key = value;
+    Assign {
+        target: Box<Spanned<Expr>>,
+        value: Box<Spanned<Expr>>,
     },
 
+    // x += value; arr[i()] -= value; the indexes in the target are only evaluated once
+    CompoundAssign {
+        op: BinaryOp,
+        target: Box<Spanned<Expr>>,
+        value: Box<Spanned<Expr>>,
+    },
+
+    // f(x, timeout: 5), the named args come after the others
     Call {
         callee: Box<Spanned<Expr>>,
         args: Vec<Spanned<Expr>>,
+        named: Vec<(Spanned<String>, Spanned<Expr>)>,
     },
 
     PropertyAccess {
@@ -54,19 +80,58 @@ pub enum Expr {
         index: Box<Spanned<Expr>>, // either abc[0] or abc["key"]
     },
 
-    Block(Vec<Spanned<Expr>>),
+    // { statements; value }, the expression at the end without a semicolon is what the
+    // block evaluates to, null if there is none
+    Block {
+        statements: Vec<Spanned<Expr>>,
+        value: Option<Box<Spanned<Expr>>>,
+    },
     Array(Vec<Spanned<Expr>>),
 
-    InternalFunction {
+    // f"a {b} c", the text and the expressions in order, joined into one string
+    Interpolation(Vec<Spanned<Expr>>),
+
+    Function {
         name: String,
-        args: Vec<String>, // Vec<"__args__"> for an optional amount
-        func: fn(Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)>,
+        args: Vec<String>,
+        arity: Arity,
+        signature: Signature,
+        body: Rc<Spanned<Expr>>,
+        slot: Option<Slot>,
+        // args take the first slots of the frame, locals in the body the rest
+        frame_size: usize,
     },
 
-    Function {
+    // struct Point { x, y, fn length(self) { } }, class Point { } is the same
+    Struct {
+        name: String,
+        fields: Vec<String>,
+        methods: Vec<Method>,
+        slot: Option<Slot>,
+    },
+
+    // enum Shape { Circle(r), Rect(w, h), Empty }, every variant with the names of its values
+    Enum {
         name: String,
+        variants: Vec<(String, Vec<String>)>,
+        slot: Option<Slot>,
+    },
+
+    // the body of fn f(a, b = 10) starts with one of these for b, it sets the arg to the
+    // value when the call left it out. args are the first slots, so index is the slot too
+    Default {
+        name: String,
+        index: usize,
+        value: Box<Spanned<Expr>>,
+    },
+
+    // fn(x) { } used as a value, evaluates to a function that captures its surroundings
+    Lambda {
         args: Vec<String>,
-        body: Box<Spanned<Expr>>,
+        arity: Arity,
+        signature: Signature,
+        body: Rc<Spanned<Expr>>,
+        frame_size: usize,
     },
 
     // import "module" as module; Ok cool but i didnt ask
@@ -77,15 +142,10 @@ pub enum Expr {
         import_as: Option<String>,
     },
 
-    Module {
-        symbols: HashMap<String, Spanned<Expr>>,
-    },
-
-    #[cfg(not(target_arch = "wasm32"))]
-    FFILibrary(Arc<Library>),
-
-    Object {
-        properties: HashMap<String, Expr>,
+    // { name: "x", "quoted key": 1, [computed]: 2, shorthand }
+    // keys are expressions so computed keys work, static keys are just Expr::String
+    ObjectLiteral {
+        properties: Vec<(Spanned<Expr>, Spanned<Expr>)>,
     },
 
     If {
@@ -94,14 +154,49 @@ pub enum Expr {
         else_branch: Option<Box<Spanned<Expr>>>,
     },
 
+    // all loops can have a label like 'outer: loop { }, used by break 'outer;
     InfiniteLoop {
         body: Box<Spanned<Expr>>,
+        label: Option<String>,
     },
 
+    // for x = iterable { } or for i, x = iterable { } to also get the index
     ForLoop {
         iterator_name: String,
+        index_name: Option<String>,
+        iterator_slot: Option<Slot>,
+        index_slot: Option<Slot>,
         iterator_range: Box<Spanned<Expr>>,
         body: Box<Spanned<Expr>>,
+        label: Option<String>,
+        // when the body makes closures every iteration runs in a new frame of this size,
+        // so each closure keeps the values of its own iteration
+        scope_size: Option<usize>,
+    },
+
+    WhileLoop {
+        condition: Box<Spanned<Expr>>,
+        body: Box<Spanned<Expr>>,
+        label: Option<String>,
+    },
+
+    // throw "message"; or throw e; to pass on an error that was caught
+    Throw(Box<Spanned<Expr>>),
+
+    // try { } catch e { } finally { }, catch and finally are both optional but not at the same time
+    // the name after catch is optional too, catch { } ignores the error
+    Try {
+        body: Box<Spanned<Expr>>,
+        catch_name: Option<String>,
+        catch_slot: Option<Slot>,
+        catch_body: Option<Box<Spanned<Expr>>>,
+        finally_body: Option<Box<Spanned<Expr>>>,
+    },
+
+    // match value { pattern if guard => body, ... }, the first arm that matches is used
+    Match {
+        value: Box<Spanned<Expr>>,
+        arms: Vec<MatchArm>,
     },
 
     Range {
@@ -120,81 +215,156 @@ pub enum Expr {
     LessThanOrEqual(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
     GreaterThan(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
     GreaterThanOrEqual(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+
+    // right side is only evaluated when the left side doesnt decide the result
+    And(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    Or(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
+    Not(Box<Spanned<Expr>>),
 }
 
-impl std::fmt::Display for Expr {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Expr::Int(n) => write!(f, "{}", n),
-            Expr::Float(fl) => write!(f, "{}", fl),
-            Expr::String(s) => {
-                let processed = Self::process_escape_sequences(s);
-                write!(f, "{}", processed)
-            },
-            Expr::Identifier(name) => write!(f, "{}", name),
-            Expr::Bool(b) => write!(f, "{}", b),
-            Expr::Null => write!(f, "null"),
-
-            Expr::Array(elements) => {
-                write!(f, "[")?;
-
-                for (i, element) in elements.iter().enumerate() {
-                    if let Expr::String(s) = &element.node {
-                        let processed = Self::process_escape_sequences(s);
-                        write!(f, "\"{}\"", processed)?;
-                    } else {
-                        write!(f, "{}", element.node)?;
-                    }
-
-                    if i != elements.len() - 1 {
-                        write!(f, ", ")?;
-                    }
-                }
-
-                write!(f, "]")
-            }
-
-            _ => write!(f, "{:?}", self),
+// the left side of a destructuring let, also used for the bindings of a for loop and match arms
+// a let can use every kind, but the value has to match or it errors
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    // _ matches anything and binds nothing
+    Wildcard,
+
+    // 200, "text", true, null, compared like ==
+    Literal(Value),
+
+    // 400..500 and 400..=499, matches ints and floats in the range
+    Range {
+        start: i64,
+        end: i64,
+        inclusive: bool,
+    },
+
+    // "a" | "b", every alternative should bind the same names
+    Or(Vec<Spanned<Pattern>>),
+
+    // slot is None when the name is a global
+    Binding {
+        name: String,
+        slot: Option<Slot>,
+    },
+
+    // [a, b, ...rest], without a rest the array has to have exactly as many elements
+    Array {
+        elements: Vec<Spanned<Pattern>>,
+        rest: Option<Box<Spanned<Pattern>>>,
+    },
+
+    // { name, age: years }, the shorthand binds a variable named like the key
+    Object {
+        properties: Vec<(Spanned<String>, Spanned<Pattern>)>,
+    },
+
+    // Shape.Circle(r) matches by the names of the enum and variant,
+    // Shape.Circle without parentheses matches any circle
+    Variant {
+        enum_name: String,
+        variant: Spanned<String>,
+        values: Option<Vec<Spanned<Pattern>>>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Spanned<Pattern>,
+    pub guard: Option<SpannedExpr>,
+    pub body: SpannedExpr,
+}
+
+#[derive(Debug, Clone)]
+pub struct Method {
+    pub name: String,
+    pub args: Vec<String>,
+    pub arity: Arity,
+    pub signature: Signature,
+    pub body: Rc<Spanned<Expr>>,
+    pub frame_size: usize,
+}
+
+// how many arguments fn f(a, b = 10, ...rest) takes, args has every name with rest last
+// max is None when there is a rest arg, it gets the ones past the others as an array
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arity {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl Arity {
+    pub fn exact(count: usize) -> Self {
+        Arity { min: count, max: Some(count) }
+    }
+
+    // what is left for the call itself, when self is passed for a method
+    pub fn without_self(self) -> Self {
+        Arity { min: self.min.saturating_sub(1), max: self.max.map(|max| max.saturating_sub(1)) }
+    }
+
+    pub fn accepts(self, count: usize) -> bool {
+        count >= self.min && self.max.is_none_or(|max| count <= max)
+    }
+}
+
+// the types in fn f(x: int, y) -> string, params has one entry for every arg
+// running ignores them, they are only checked by modu check (see checker.rs)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Signature {
+    pub params: Vec<Option<Spanned<Type>>>,
+    pub returns: Option<Spanned<Type>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Int,
+    Float,
+    String,
+    Bool,
+    Null,
+    Array,
+    Object,
+    Range,
+    Function,
+    // anything goes, what values without a known type are too
+    Any,
+    // a struct or an enum
+    Named(String),
+}
+
+impl Type {
+    pub fn from_name(name: &str) -> Type {
+        match name {
+            "int" => Type::Int,
+            "float" => Type::Float,
+            "string" => Type::String,
+            "bool" => Type::Bool,
+            "null" => Type::Null,
+            "array" => Type::Array,
+            "object" => Type::Object,
+            "range" => Type::Range,
+            "function" => Type::Function,
+            "any" => Type::Any,
+            name => Type::Named(name.to_string()),
         }
     }
 }
 
-impl Expr {
-    fn process_escape_sequences(s: &str) -> String {
-        let mut result = String::new();
-        let mut chars = s.chars();
-        
-        while let Some(ch) = chars.next() {
-            if ch == '\\' {
-                if let Some(next) = chars.next() {
-                    match next {
-                        'n' => result.push('\n'),
-                        't' => result.push('\t'),
-                        '"' => result.push('"'),
-                        '\\' => result.push('\\'),
-                        'x' => {
-                            let hex: String = chars.by_ref().take(2).collect();
-                            if let Ok(byte) = u8::from_str_radix(&hex, 16) {
-                                result.push(byte as char);
-                            } else {
-                                result.push('\\');
-                                result.push('x');
-                                result.push_str(&hex);
-                            }
-                        }
-                        _ => {
-                            result.push('\\');
-                            result.push(next);
-                        }
-                    }
-                } else {
-                    result.push('\\');
-                }
-            } else {
-                result.push(ch);
-            }
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "float"),
+            Type::String => write!(f, "string"),
+            Type::Bool => write!(f, "bool"),
+            Type::Null => write!(f, "null"),
+            Type::Array => write!(f, "array"),
+            Type::Object => write!(f, "object"),
+            Type::Range => write!(f, "range"),
+            Type::Function => write!(f, "function"),
+            Type::Any => write!(f, "any"),
+            Type::Named(name) => write!(f, "{}", name),
         }
-        
-        result
     }
 }
@@ -1,29 +1,30 @@
-use crate::{ast::{Expr, InternalFunctionResponse, Spanned}, lexer::Span};
+use std::rc::Rc;
+use crate::{ast::Spanned, lexer::Span, value::{InternalFunction, InternalFunctionResponse, Value}};
 
-pub fn len(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn len(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let array = match &args[0].node {
-        Expr::Array(elements) => elements,
+        Value::Array(elements) => elements,
         _ => unreachable!(),
     };
 
     let length = array.len() as i64;
     
     Ok(InternalFunctionResponse {
-        return_value: Expr::Int(length),
+        return_value: Value::Int(length),
         replace_self: None,
     })
 }
 
-pub fn clear(_: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn clear(_: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     Ok(InternalFunctionResponse {
-        return_value: Expr::Null,
-        replace_self: Some(Expr::Array(vec![])),
+        return_value: Value::Null,
+        replace_self: Some(Value::Array(Rc::new(vec![]))),
     })
 }
 
-pub fn push(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn push(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let array = match &args[0].node {
-        Expr::Array(elements) => elements,
+        Value::Array(elements) => elements,
         _ => unreachable!(),
     };
 
@@ -35,17 +36,17 @@ pub fn push(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Strin
     }
 
     let mut new_array = array.clone();
-    new_array.push(args[1].clone());
+    Rc::make_mut(&mut new_array).push(args[1].node.clone());
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::Null,
-        replace_self: Some(Expr::Array(new_array)),
+        return_value: Value::Null,
+        replace_self: Some(Value::Array(new_array)),
     })
 }
 
-pub fn pop(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn pop(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let array = match &args[0].node {
-        Expr::Array(elements) => elements,
+        Value::Array(elements) => elements,
         _ => unreachable!(),
     };
 
@@ -57,16 +58,16 @@ pub fn pop(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     }
 
     let mut new_array = array.clone();
-    let popped_element = new_array.pop().unwrap();
+    let popped_element = Rc::make_mut(&mut new_array).pop().unwrap();
 
     Ok(InternalFunctionResponse {
-        return_value: popped_element.node,
-        replace_self: Some(Expr::Array(new_array)),
+        return_value: popped_element,
+        replace_self: Some(Value::Array(new_array)),
     })
 }
 
-pub fn get_fn(name: &str) -> Option<Expr> {
-    Some(Expr::InternalFunction {
+pub fn get_fn(name: &str) -> Option<Value> {
+    Some(Value::InternalFunction(Rc::new(InternalFunction {
         name: name.to_string(),
         args: match name {
             "len" => vec!["self".to_string()],
@@ -82,5 +83,5 @@ pub fn get_fn(name: &str) -> Option<Expr> {
             "pop" => pop,
             _ => return None,
         },
-    })
+    })))
 }
\ No newline at end of file
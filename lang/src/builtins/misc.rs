@@ -1,12 +1,12 @@
-use std::collections::HashMap;
-use crate::{ast::{Expr, InternalFunctionResponse, Spanned}, lexer::Span};
+use rustc_hash::FxHashMap;
+use crate::{ast::Spanned, lexer::Span, value::{InternalFunctionResponse, Value}};
 
 #[cfg(target_arch = "wasm32")]
 unsafe extern "C" {
     fn _modu_print(ptr: *const u8, len: usize);
 }
 
-pub fn print(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn print(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let mut output = String::new();
 
     for arg in args {
@@ -28,12 +28,12 @@ pub fn print(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Stri
     }
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::Null,
+        return_value: Value::Null,
         replace_self: None,
     })
 }
 
-pub fn input(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn input(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     use std::io::{self, Write};
 
     for arg in args.clone() {
@@ -48,22 +48,22 @@ pub fn input(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Stri
     io::stdin().read_line(&mut input).map_err(|e| (format!("Failed to read input: {}", e), span))?;
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(input.trim_end().to_string()),
+        return_value: Value::String(input.trim_end().into()),
         replace_self: None,
     })
 }
 
-pub fn exit(_: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn exit(_: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     std::process::exit(0);
 }
 
-pub fn str(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn str(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let string = match &args[0].node {
-        Expr::Int(n) => n.to_string(),
-        Expr::Float(f) => f.to_string(),
-        Expr::String(s) => s.clone(),
-        Expr::Bool(b) => b.to_string(),
-        Expr::Null => "null".to_string(),
+        Value::Int(n) => n.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::String(s) => s.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => "null".to_string(),
         _ => return Err((
             format!("Cannot convert {:?} to string", args[0].node),
             args[0].span,
@@ -71,20 +71,20 @@ pub fn str(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     }; 
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(string),
+        return_value: Value::String(string.into()),
         replace_self: None,
     })
 }
 
-pub fn int(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn int(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let integer = match &args[0].node {
-        Expr::Int(n) => *n,
-        Expr::Float(f) => *f as i64,
-        Expr::String(s) => s.parse::<i64>().map_err(|e| (
+        Value::Int(n) => *n,
+        Value::Float(f) => *f as i64,
+        Value::String(s) => s.parse::<i64>().map_err(|e| (
             format!("Could not convert string to int: {}", e),
             args[0].span,
         ))?,
-        Expr::Bool(b) => if *b { 1 } else { 0 },
+        Value::Bool(b) => if *b { 1 } else { 0 },
         _ => return Err((
             format!("Cannot convert {:?} to int", args[0].node),
             args[0].span,
@@ -92,20 +92,20 @@ pub fn int(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     }; 
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::Int(integer),
+        return_value: Value::Int(integer),
         replace_self: None,
     })
 }
 
-pub fn float(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn float(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let float = match &args[0].node {
-        Expr::Int(n) => *n as f64,
-        Expr::Float(f) => *f,
-        Expr::String(s) => s.parse::<f64>().map_err(|e| (
+        Value::Int(n) => *n as f64,
+        Value::Float(f) => *f,
+        Value::String(s) => s.parse::<f64>().map_err(|e| (
             format!("Could not convert string to float: {}", e),
             args[0].span,
         ))?,
-        Expr::Bool(b) => if *b { 1.0 } else { 0.0 },
+        Value::Bool(b) => if *b { 1.0 } else { 0.0 },
         _ => return Err((
             format!("Cannot convert {:?} to float", args[0].node),
             args[0].span,
@@ -113,18 +113,18 @@ pub fn float(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Stri
     }; 
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::Float(float),
+        return_value: Value::Float(float),
         replace_self: None,
     })
 }
 
-pub fn bool(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn bool(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let boolean = match &args[0].node {
-        Expr::Int(n) => *n != 0,
-        Expr::Float(f) => *f != 0.0,
-        Expr::String(s) => s == "true",
-        Expr::Bool(b) => *b,
-        Expr::Null => false,
+        Value::Int(n) => *n != 0,
+        Value::Float(f) => *f != 0.0,
+        Value::String(s) => &**s == "true",
+        Value::Bool(b) => *b,
+        Value::Null => false,
         _ => return Err((
             format!("Cannot convert {:?} to bool", args[0].node),
             args[0].span,
@@ -132,72 +132,44 @@ pub fn bool(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Strin
     }; 
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::Bool(boolean),
+        return_value: Value::Bool(boolean),
         replace_self: None,
     })
 }
 
-pub fn fill_context(context: &mut HashMap<String, Expr>) {
+pub fn fill_context(context: &mut FxHashMap<String, Value>) {
     context.insert(
         "print".to_string(),
-        Expr::InternalFunction {
-            name: "print".to_string(),
-            args: vec!["__args__".to_string()],
-            func: print,
-        },
+        Value::internal_function("print", &["__args__"], print),
     );
 
     context.insert(
         "input".to_string(),
-        Expr::InternalFunction {
-            name: "input".to_string(),
-            args: vec!["__args__".to_string()],
-            func: input,
-        },
+        Value::internal_function("input", &["__args__"], input),
     );
 
     context.insert(
         "exit".to_string(),
-        Expr::InternalFunction {
-            name: "exit".to_string(),
-            args: vec!["__args__".to_string()],
-            func: exit,
-        },
+        Value::internal_function("exit", &["__args__"], exit),
     );
 
     context.insert(
         "str".to_string(),
-        Expr::InternalFunction {
-            name: "str".to_string(),
-            args: vec!["value".to_string()],
-            func: str,
-        },
+        Value::internal_function("str", &["value"], str),
     );
 
     context.insert(
         "int".to_string(),
-        Expr::InternalFunction {
-            name: "int".to_string(),
-            args: vec!["value".to_string()],
-            func: int,
-        },
+        Value::internal_function("int", &["value"], int),
     );
 
     context.insert(
         "float".to_string(),
-        Expr::InternalFunction {
-            name: "float".to_string(),
-            args: vec!["value".to_string()],
-            func: float,
-        },
+        Value::internal_function("float", &["value"], float),
     );
 
     context.insert(
         "bool".to_string(),
-        Expr::InternalFunction {
-            name: "bool".to_string(),
-            args: vec!["value".to_string()],
-            func: bool,
-        },
+        Value::internal_function("bool", &["value"], bool),
     );
 }
\ No newline at end of file
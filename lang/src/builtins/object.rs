@@ -1,8 +1,9 @@
-use crate::{ast::{Expr, InternalFunctionResponse, Spanned}, lexer::Span};
+use std::rc::Rc;
+use crate::{ast::Spanned, lexer::Span, value::{InternalFunction, InternalFunctionResponse, Value}};
 
-pub fn get(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn get(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let object = match &args[0].node {
-        Expr::Object { properties } => properties,
+        Value::Object(properties) => properties,
         _ => {
             return Err((
                 "get expects an object as the first argument".to_string(),
@@ -12,7 +13,7 @@ pub fn get(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     };
 
     let key = match &args[1].node {
-        Expr::String(s) => s,
+        Value::String(s) => s,
         _ => {
             return Err((
                 "get expects a string as the second argument".to_string(),
@@ -21,7 +22,7 @@ pub fn get(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
         }
     };
 
-    match object.get(key) {
+    match object.get(&**key) {
         Some(value) => Ok(InternalFunctionResponse {
             return_value: value.clone(),
             replace_self: None,
@@ -34,9 +35,9 @@ pub fn get(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     }
 }
 
-pub fn set(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn set(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let object = match &args[0].node {
-        Expr::Object { properties } => properties.clone(),
+        Value::Object(properties) => properties.clone(),
         _ => {
             return Err((
                 "set expects an object as the first argument".to_string(),
@@ -46,7 +47,7 @@ pub fn set(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     };
 
     let key = match &args[1].node {
-        Expr::String(s) => s.clone(),
+        Value::String(s) => s.to_string(),
         _ => {
             return Err((
                 "set expects a string as the second argument".to_string(),
@@ -56,19 +57,17 @@ pub fn set(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     };
 
     let mut new_properties = object;
-    new_properties.insert(key, args[2].node.clone());
+    Rc::make_mut(&mut new_properties).insert(key, args[2].node.clone());
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::Null,
-        replace_self: Some(Expr::Object {
-            properties: new_properties,
-        }),
+        return_value: Value::Null,
+        replace_self: Some(Value::Object(new_properties)),
     })
 }
 
-pub fn has(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn has(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let object = match &args[0].node {
-        Expr::Object { properties } => properties,
+        Value::Object(properties) => properties,
         _ => {
             return Err((
                 "has expects an object as the first argument".to_string(),
@@ -78,7 +77,7 @@ pub fn has(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     };
 
     let key = match &args[1].node {
-        Expr::String(s) => s,
+        Value::String(s) => s,
         _ => {
             return Err((
                 "has expects a string as the second argument".to_string(),
@@ -87,17 +86,17 @@ pub fn has(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
         }
     };
 
-    let exists = object.contains_key(key);
+    let exists = object.contains_key(&**key);
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::Bool(exists),
+        return_value: Value::Bool(exists),
         replace_self: None,
     })
 }
 
-pub fn delete(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn delete(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let object = match &args[0].node {
-        Expr::Object { properties } => properties.clone(),
+        Value::Object(properties) => properties.clone(),
         _ => {
             return Err((
                 "delete expects an object as the first argument".to_string(),
@@ -107,7 +106,7 @@ pub fn delete(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Str
     };
 
     let key = match &args[1].node {
-        Expr::String(s) => s.clone(),
+        Value::String(s) => s.clone(),
         _ => {
             return Err((
                 "delete expects a string as the second argument".to_string(),
@@ -117,29 +116,27 @@ pub fn delete(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Str
     };
 
     let mut new_properties = object;
-    new_properties.remove(&key);
+    Rc::make_mut(&mut new_properties).remove(&*key);
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::Null,
-        replace_self: Some(Expr::Object {
-            properties: new_properties,
-        }),
+        return_value: Value::Null,
+        replace_self: Some(Value::Object(new_properties)),
     })
 }
 
-pub fn to_string(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn to_string(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let object = &args[0].node;
 
     let result = match object {
-        Expr::Object { properties } => {
+        Value::Object(properties) => {
             let mut parts = vec![];
-            for (key, value) in properties {
+            for (key, value) in properties.iter() {
                 let value_str = match value {
-                    Expr::String(s) => format!("\"{}\"", s),
-                    Expr::Int(n) => n.to_string(),
-                    Expr::Float(f) => f.to_string(),
-                    Expr::Bool(b) => b.to_string(),
-                    Expr::Null => "null".to_string(),
+                    Value::String(s) => format!("\"{}\"", s),
+                    Value::Int(n) => n.to_string(),
+                    Value::Float(f) => f.to_string(),
+                    Value::Bool(b) => b.to_string(),
+                    Value::Null => "null".to_string(),
                     _ => "\"<complex_value>\"".to_string(),
                 };
                 parts.push(format!("\"{}\": {}", key, value_str));
@@ -155,13 +152,13 @@ pub fn to_string(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (
     };
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(result),
+        return_value: Value::String(result.into()),
         replace_self: None,
     })
 }
 
-pub fn get_fn(name: &str) -> Option<Expr> {
-    Some(Expr::InternalFunction {
+pub fn get_fn(name: &str) -> Option<Value> {
+    Some(Value::InternalFunction(Rc::new(InternalFunction {
         name: name.to_string(),
         args: match name {
             "get" => vec!["self".to_string(), "key".to_string()],
@@ -179,5 +176,5 @@ pub fn get_fn(name: &str) -> Option<Expr> {
             "to_string" => to_string,
             _ => return None,
         },
-    })
+    })))
 }
\ No newline at end of file
@@ -0,0 +1,759 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{Expr, Pattern, Signature, Spanned, SpannedExpr, Type};
+use crate::eval::BinaryOp;
+use crate::lexer::Span;
+
+// modu check, looks for type errors before anything runs
+//
+// the types come from annotations (let x: int, fn f(x: int) -> string), literals and
+// operators, anything else is Any and never causes an error, so code without
+// annotations only gets errors for things that always fail, like 1 + "a"
+// it runs on the parsed ast before the resolver, variables are looked up by name
+//
+// an assignment gives a variable its new type, and where two ways through the code
+// meet (after an if, a match or a loop) it gets the join of the types each left it with
+
+pub struct TypeError {
+    pub message: String,
+    pub message_short: String,
+    pub span: Span,
+    // the annotation that asked for another type, if that is what went wrong
+    pub expected_at: Option<Span>,
+}
+
+#[derive(Clone, PartialEq)]
+enum Variable {
+    // annotation is where the type was written, assignments then have to fit it
+    // without one the type is whatever was assigned last
+    Value { ty: Type, annotation: Option<Span> },
+    // the names of the args, for named args, and their types
+    // also a variable holding a function, like let f = fn(x: int) { }, until it is assigned again
+    Function(Vec<String>, Signature),
+    Struct,
+    Enum,
+}
+
+impl Variable {
+    fn ty(&self) -> Type {
+        match self {
+            Variable::Value { ty, .. } => ty.clone(),
+            Variable::Function(..) => Type::Function,
+            Variable::Struct | Variable::Enum => Type::Any,
+        }
+    }
+}
+
+type Scopes = Vec<HashMap<String, Variable>>;
+
+struct Checker {
+    scopes: Scopes,
+    // the return type of every function being checked, innermost last
+    returns: Vec<Option<Spanned<Type>>>,
+    // the first scope of every function being checked, innermost last
+    functions: Vec<usize>,
+    // variables a function assigns to from outside of it, by the index of their scope
+    // the function can run whenever it is called, so their type is Any from then on
+    captured: HashSet<(usize, String)>,
+    errors: Vec<TypeError>,
+}
+
+pub fn check(ast: &[SpannedExpr]) -> Vec<TypeError> {
+    let mut checker = Checker {
+        scopes: vec![HashMap::new()],
+        returns: vec![],
+        functions: vec![],
+        captured: HashSet::new(),
+        errors: vec![],
+    };
+
+    checker.statements(ast);
+    checker.errors
+}
+
+// an int is fine where a float is expected, they mix in math anyway
+fn fits(found: &Type, expected: &Type) -> bool {
+    found == expected || *found == Type::Any || *expected == Type::Any || (*found == Type::Int && *expected == Type::Float)
+}
+
+fn is_number(ty: &Type) -> bool {
+    matches!(ty, Type::Int | Type::Float)
+}
+
+// the type of something that is one of two values, like the branches of an if
+fn join(a: Type, b: Type) -> Type {
+    match (a, b) {
+        (a, b) if a == b => a,
+        (a, b) if is_number(&a) && is_number(&b) => Type::Float,
+        _ => Type::Any,
+    }
+}
+
+// whether running the expression always ends in a return or a throw
+fn always_returns(expr: &SpannedExpr) -> bool {
+    match &expr.node {
+        Expr::Return(_) | Expr::Throw(_) => true,
+        Expr::Block { statements, value } => statements.iter().any(always_returns) || value.as_deref().is_some_and(always_returns),
+        Expr::If { then_branch, else_branch: Some(else_branch), .. } => always_returns(then_branch) && always_returns(else_branch),
+        // a value no arm matches is an error, so only the arms matter
+        Expr::Match { arms, .. } => arms.iter().all(|arm| always_returns(&arm.body)),
+        // loop { } is taken to be left with return, a break out of it isnt looked for
+        Expr::InfiniteLoop { .. } => true,
+
+        Expr::Try { body, catch_body, finally_body, .. } => {
+            finally_body.as_deref().is_some_and(always_returns)
+                || (always_returns(body) && catch_body.as_deref().is_none_or(always_returns))
+        }
+
+        _ => false,
+    }
+}
+
+fn bindings<'a>(pattern: &'a Spanned<Pattern>, names: &mut Vec<&'a str>) {
+    match &pattern.node {
+        Pattern::Binding { name, .. } => names.push(name),
+        Pattern::Array { elements, rest } => {
+            for element in elements.iter().chain(rest.as_deref()) {
+                bindings(element, names);
+            }
+        }
+        Pattern::Object { properties } => {
+            for (_, property) in properties {
+                bindings(property, names);
+            }
+        }
+        // every alternative binds the same names
+        Pattern::Or(alternatives) => bindings(&alternatives[0], names),
+        Pattern::Variant { values, .. } => {
+            for value in values.iter().flatten() {
+                bindings(value, names);
+            }
+        }
+        Pattern::Wildcard | Pattern::Literal(_) | Pattern::Range { .. } => {}
+    }
+}
+
+impl Checker {
+    fn error(&mut self, message: String, message_short: String, span: Span) {
+        self.errors.push(TypeError { message, message_short, span, expected_at: None });
+    }
+
+    fn mismatch(&mut self, found: &Type, expected: &Spanned<Type>, span: Span) {
+        self.errors.push(TypeError {
+            message: format!("Expected {}, found {}", expected.node, found),
+            message_short: format!("found {}", found),
+            span,
+            expected_at: Some(expected.span),
+        });
+    }
+
+    fn declare(&mut self, name: &str, variable: Variable) {
+        self.scopes.last_mut().unwrap().insert(name.to_string(), variable);
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Variable> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    // a variable with a type annotation has to keep it, one without takes on the new type
+    fn assign(&mut self, name: &str, value: Variable, span: Span) {
+        let Some(index) = self.scopes.iter().rposition(|scope| scope.contains_key(name)) else {
+            return;
+        };
+
+        if self.functions.last().is_some_and(|base| index < *base) {
+            self.captured.insert((index, name.to_string()));
+        }
+
+        let captured = self.captured.contains(&(index, name.to_string()));
+        let variable = self.scopes[index].get_mut(name).unwrap();
+
+        if let Variable::Value { ty: expected, annotation: Some(at) } = variable {
+            let expected = Spanned { node: expected.clone(), span: *at };
+
+            if !fits(&value.ty(), &expected.node) {
+                self.mismatch(&value.ty(), &expected, span);
+            }
+
+            return;
+        }
+
+        *variable = match captured {
+            true => Variable::Value { ty: Type::Any, annotation: None },
+            false => value,
+        };
+    }
+
+    // after two ways through the code meet, each variable could have the type either left it with
+    fn merge(&mut self, other: Scopes) {
+        for (scope, other) in self.scopes.iter_mut().zip(other) {
+            for (name, variable) in scope.iter_mut() {
+                if let Some(other) = other.get(name) && variable != other {
+                    let annotation = match variable {
+                        Variable::Value { annotation, .. } => *annotation,
+                        _ => None,
+                    };
+
+                    *variable = Variable::Value { ty: join(variable.ty(), other.ty()), annotation };
+                }
+            }
+        }
+    }
+
+    // a loop body can run any number of times, so it is checked without reporting anything
+    // until the types it leaves the variables with stop changing, and then once for real
+    fn loop_body(&mut self, body: &SpannedExpr) {
+        let errors = self.errors.len();
+
+        loop {
+            let before = self.scopes.clone();
+            self.expr(body);
+            self.merge(before.clone());
+
+            if self.scopes == before {
+                break;
+            }
+        }
+
+        self.errors.truncate(errors);
+
+        let before = self.scopes.clone();
+        self.expr(body);
+        self.merge(before);
+    }
+
+    // let f = fn(x: int) { } and let g = f; keep the signature so calls to them are checked
+    fn function_value(&self, value: &SpannedExpr) -> Option<Variable> {
+        match &value.node {
+            Expr::Lambda { args, signature, .. } => Some(Variable::Function(args.clone(), signature.clone())),
+            Expr::Identifier(name) => self.lookup(name).filter(|variable| matches!(variable, Variable::Function(..))).cloned(),
+            _ => None,
+        }
+    }
+
+    // struct and enum names are types too, the rest are builtin
+    // an unknown type is reported once and then treated as Any
+    fn annotation(&mut self, ty: &Spanned<Type>) -> Spanned<Type> {
+        if let Type::Named(name) = &ty.node && !matches!(self.lookup(name), Some(Variable::Struct | Variable::Enum)) {
+            self.error(format!("Unknown type {}", name), "unknown type".to_string(), ty.span);
+            return Spanned { node: Type::Any, span: ty.span };
+        }
+
+        ty.clone()
+    }
+
+    fn statements(&mut self, statements: &[SpannedExpr]) {
+        // functions, structs and enums can be used before the line that declares them,
+        // from inside functions that are called later
+        for statement in statements {
+            match &statement.node {
+                Expr::Function { name, args, signature, .. } => self.declare(name, Variable::Function(args.clone(), signature.clone())),
+                Expr::Struct { name, .. } => self.declare(name, Variable::Struct),
+                Expr::Enum { name, .. } => self.declare(name, Variable::Enum),
+                _ => {}
+            }
+        }
+
+        for statement in statements {
+            self.expr(statement);
+        }
+    }
+
+    fn function(&mut self, args: &[String], signature: &Signature, body: &SpannedExpr) {
+        self.functions.push(self.scopes.len());
+        self.scopes.push(HashMap::new());
+
+        for (i, arg) in args.iter().enumerate() {
+            let annotation = signature.params.get(i).cloned().flatten().map(|annotation| self.annotation(&annotation));
+
+            self.declare(arg, Variable::Value {
+                ty: annotation.as_ref().map_or(Type::Any, |annotation| annotation.node.clone()),
+                annotation: annotation.map(|annotation| annotation.span),
+            });
+        }
+
+        let returns = signature.returns.as_ref().map(|returns| self.annotation(returns));
+        self.returns.push(returns.clone());
+
+        // the value the body ends with is returned, a return statement is checked where it is
+        match &body.node {
+            Expr::Block { statements, value } => {
+                self.statements(statements);
+
+                if let Some(value) = value {
+                    let ty = self.expr(value);
+
+                    if let Some(returns) = &returns && !fits(&ty, &returns.node) {
+                        self.mismatch(&ty, returns, value.span);
+                    }
+                }
+            }
+
+            _ => { self.expr(body); }
+        }
+
+        // falling off the end returns null, which only a null or any return type allows
+        if let Some(returns) = &returns
+            && !matches!(returns.node, Type::Null | Type::Any)
+            && !matches!(&body.node, Expr::Block { value: Some(_), .. })
+            && !always_returns(body)
+        {
+            self.errors.push(TypeError {
+                message: format!("Missing return, the function has to return {} but can reach its end", returns.node),
+                message_short: "ends without returning".to_string(),
+                span: Span::from(body.span.end - 1..body.span.end),
+                expected_at: Some(returns.span),
+            });
+        }
+
+        self.returns.pop();
+        self.functions.pop();
+        self.scopes.pop();
+    }
+
+    fn binary(&mut self, verb: &str, left: &SpannedExpr, right: &SpannedExpr, span: Span) -> Type {
+        let l = self.expr(left);
+        let r = self.expr(right);
+
+        match (&l, &r) {
+            (Type::Int, Type::Int) => Type::Int,
+            (l, r) if is_number(l) && is_number(r) => Type::Float,
+            (Type::String, Type::String) if verb == "add" => Type::String,
+            (Type::Any, _) | (_, Type::Any) => Type::Any,
+
+            _ => {
+                self.error(format!("Cannot {} {} and {}", verb, l, r), format!("cannot {}", verb), span);
+                Type::Any
+            }
+        }
+    }
+
+    fn compare(&mut self, left: &SpannedExpr, right: &SpannedExpr, span: Span) -> Type {
+        let l = self.expr(left);
+        let r = self.expr(right);
+
+        if !(is_number(&l) || l == Type::Any) || !(is_number(&r) || r == Type::Any) {
+            self.error(format!("Cannot compare {} and {}", l, r), "cannot compare".to_string(), span);
+        }
+
+        Type::Bool
+    }
+
+    fn range_bound(&mut self, bound: &SpannedExpr, which: &str) {
+        let ty = self.expr(bound);
+
+        if !fits(&ty, &Type::Int) {
+            self.error(format!("Range {} must be an int, found {}", which, ty), format!("found {}", ty), bound.span);
+        }
+    }
+
+    fn expr(&mut self, expr: &SpannedExpr) -> Type {
+        match &expr.node {
+            Expr::Int(_) => Type::Int,
+            Expr::Float(_) => Type::Float,
+            Expr::String(_) => Type::String,
+            Expr::Bool(_) => Type::Bool,
+            Expr::Null => Type::Null,
+
+            Expr::Interpolation(parts) => {
+                for part in parts {
+                    self.expr(part);
+                }
+
+                Type::String
+            }
+
+            Expr::Identifier(name) => match self.lookup(name) {
+                Some(Variable::Value { ty, .. }) => ty.clone(),
+                Some(Variable::Function(..)) => Type::Function,
+                // globals like print and imported modules
+                _ => Type::Any,
+            },
+
+            Expr::Local { .. } => Type::Any,
+
+            Expr::Neg(value) => {
+                let ty = self.expr(value);
+
+                if !is_number(&ty) && ty != Type::Any {
+                    self.error(format!("Cannot negate {}", ty), "cannot negate".to_string(), expr.span);
+                    return Type::Any;
+                }
+
+                ty
+            }
+
+            Expr::Add(left, right) => self.binary("add", left, right, expr.span),
+            Expr::Sub(left, right) => self.binary("subtract", left, right, expr.span),
+            Expr::Mul(left, right) => self.binary("multiply", left, right, expr.span),
+            Expr::Div(left, right) => self.binary("divide", left, right, expr.span),
+            Expr::Mod(left, right) => self.binary("take modulo of", left, right, expr.span),
+
+            Expr::LessThan(left, right)
+            | Expr::LessThanOrEqual(left, right)
+            | Expr::GreaterThan(left, right)
+            | Expr::GreaterThanOrEqual(left, right) => self.compare(left, right, expr.span),
+
+            Expr::Equal(left, right) | Expr::NotEqual(left, right) | Expr::And(left, right) | Expr::Or(left, right) => {
+                self.expr(left);
+                self.expr(right);
+                Type::Bool
+            }
+
+            Expr::Not(value) => {
+                self.expr(value);
+                Type::Bool
+            }
+
+            Expr::Let { name, value, annotation, .. } => {
+                let ty = self.expr(value);
+
+                match annotation {
+                    Some(annotation) => {
+                        let annotation = self.annotation(annotation);
+
+                        if !fits(&ty, &annotation.node) {
+                            self.mismatch(&ty, &annotation, value.span);
+                        }
+
+                        self.declare(name, Variable::Value { ty: annotation.node.clone(), annotation: Some(annotation.span) });
+                    }
+
+                    None => {
+                        let variable = self.function_value(value).unwrap_or(Variable::Value { ty, annotation: None });
+                        self.declare(name, variable);
+                    }
+                }
+
+                Type::Null
+            }
+
+            Expr::Destructure { pattern, value } => {
+                self.expr(value);
+
+                let mut names = vec![];
+                bindings(pattern, &mut names);
+
+                for name in names {
+                    self.declare(name, Variable::Value { ty: Type::Any, annotation: None });
+                }
+
+                Type::Null
+            }
+
+            Expr::Assign { target, value } => {
+                let ty = self.expr(value);
+
+                match &target.node {
+                    Expr::Identifier(name) => {
+                        let variable = self.function_value(value).unwrap_or(Variable::Value { ty, annotation: None });
+                        self.assign(name, variable, value.span);
+                    }
+
+                    _ => { self.expr(target); }
+                }
+
+                Type::Null
+            }
+
+            Expr::CompoundAssign { op, target, value } => {
+                let verb = match op {
+                    BinaryOp::Add => "add",
+                    BinaryOp::Sub => "subtract",
+                    BinaryOp::Mul => "multiply",
+                    BinaryOp::Div => "divide",
+                    _ => "take modulo of",
+                };
+
+                let ty = self.binary(verb, target, value, Span::from(target.span.start..value.span.end));
+
+                if let Expr::Identifier(name) = &target.node {
+                    self.assign(name, Variable::Value { ty, annotation: None }, value.span);
+                }
+
+                Type::Null
+            }
+
+            Expr::Call { callee, args, named } => {
+                let types = args.iter().map(|arg| self.expr(arg)).collect::<Vec<_>>();
+                let named_types = named.iter().map(|(_, arg)| self.expr(arg)).collect::<Vec<_>>();
+
+                match &callee.node {
+                    Expr::Identifier(name) => match self.lookup(name).cloned() {
+                        Some(Variable::Function(arg_names, signature)) => {
+                            for ((arg, ty), param) in args.iter().zip(&types).zip(&signature.params) {
+                                if let Some(param) = param && !fits(ty, &param.node) {
+                                    self.mismatch(ty, param, arg.span);
+                                }
+                            }
+
+                            for ((arg_name, arg), ty) in named.iter().zip(&named_types) {
+                                let Some(index) = arg_names.iter().position(|other| *other == arg_name.node) else {
+                                    self.error(format!("Function {} has no argument named {}", name, arg_name.node), "unknown argument".to_string(), arg_name.span);
+                                    continue;
+                                };
+
+                                if let Some(param) = &signature.params[index] && !fits(ty, &param.node) {
+                                    self.mismatch(ty, param, arg.span);
+                                }
+                            }
+
+                            signature.returns.map_or(Type::Any, |returns| returns.node)
+                        }
+
+                        Some(Variable::Struct) => Type::Named(name.clone()),
+                        _ => Type::Any,
+                    },
+
+                    // Shape.Circle(1)
+                    Expr::PropertyAccess { object, .. } if matches!(&object.node, Expr::Identifier(name) if matches!(self.lookup(name), Some(Variable::Enum))) => {
+                        let Expr::Identifier(name) = &object.node else { unreachable!() };
+                        Type::Named(name.clone())
+                    }
+
+                    _ => {
+                        self.expr(callee);
+                        Type::Any
+                    }
+                }
+            }
+
+            Expr::PropertyAccess { object, .. } => {
+                // Shape.Empty
+                if let Expr::Identifier(name) = &object.node && matches!(self.lookup(name), Some(Variable::Enum)) {
+                    return Type::Named(name.clone());
+                }
+
+                self.expr(object);
+                Type::Any
+            }
+
+            Expr::IndexAccess { object, index } => {
+                self.expr(object);
+                self.expr(index);
+                Type::Any
+            }
+
+            Expr::Block { statements, value } => {
+                self.scopes.push(HashMap::new());
+                self.statements(statements);
+                let ty = value.as_ref().map_or(Type::Null, |value| self.expr(value));
+                self.scopes.pop();
+
+                ty
+            }
+
+            Expr::Array(elements) => {
+                for element in elements {
+                    self.expr(element);
+                }
+
+                Type::Array
+            }
+
+            Expr::ObjectLiteral { properties } => {
+                for (key, value) in properties {
+                    self.expr(key);
+                    self.expr(value);
+                }
+
+                Type::Object
+            }
+
+            Expr::Function { name, args, signature, body, .. } => {
+                self.declare(name, Variable::Function(args.clone(), signature.clone()));
+                self.function(args, signature, body);
+                Type::Null
+            }
+
+            Expr::Lambda { args, signature, body, .. } => {
+                self.function(args, signature, body);
+                Type::Function
+            }
+
+            Expr::Struct { name, methods, .. } => {
+                self.declare(name, Variable::Struct);
+
+                for method in methods {
+                    self.function(&method.args, &method.signature, &method.body);
+                }
+
+                Type::Null
+            }
+
+            Expr::Enum { name, .. } => {
+                self.declare(name, Variable::Enum);
+                Type::Null
+            }
+
+            Expr::Import { import_as, .. } => {
+                if let Some(name) = import_as && name != "*" {
+                    self.declare(name, Variable::Value { ty: Type::Any, annotation: None });
+                }
+
+                Type::Null
+            }
+
+            Expr::If { condition, then_branch, else_branch } => {
+                self.expr(condition);
+
+                let before = self.scopes.clone();
+                let then_type = self.expr(then_branch);
+                let after_then = std::mem::replace(&mut self.scopes, before);
+                let else_type = else_branch.as_ref().map_or(Type::Null, |else_branch| self.expr(else_branch));
+
+                // a branch that returns never gives the if its value, or its variables to the code after it
+                match (always_returns(then_branch), else_branch.as_deref().is_some_and(always_returns)) {
+                    (true, false) => else_type,
+
+                    (false, true) => {
+                        self.scopes = after_then;
+                        then_type
+                    }
+
+                    _ => {
+                        self.merge(after_then);
+                        join(then_type, else_type)
+                    }
+                }
+            }
+
+            Expr::InfiniteLoop { body, .. } => {
+                self.loop_body(body);
+                Type::Null
+            }
+
+            Expr::WhileLoop { condition, body, .. } => {
+                self.expr(condition);
+                self.loop_body(body);
+                Type::Null
+            }
+
+            Expr::ForLoop { iterator_name, index_name, iterator_range, body, .. } => {
+                let item = match self.expr(iterator_range) {
+                    Type::Range => Type::Int,
+                    Type::String => Type::String,
+                    _ => Type::Any,
+                };
+
+                self.scopes.push(HashMap::new());
+                self.declare(iterator_name, Variable::Value { ty: item, annotation: None });
+
+                if let Some(index_name) = index_name {
+                    self.declare(index_name, Variable::Value { ty: Type::Int, annotation: None });
+                }
+
+                self.loop_body(body);
+                self.scopes.pop();
+
+                Type::Null
+            }
+
+            Expr::Throw(value) => {
+                self.expr(value);
+                Type::Any
+            }
+
+            // the default of an arg has to fit its type like any other value passed for it
+            Expr::Default { name, value, .. } => {
+                let ty = self.expr(value);
+
+                if let Some(Variable::Value { ty: expected, annotation: Some(at) }) = self.lookup(name).cloned() && !fits(&ty, &expected) {
+                    self.mismatch(&ty, &Spanned { node: expected, span: at }, value.span);
+                }
+
+                Type::Null
+            }
+
+            Expr::Try { body, catch_name, catch_body, finally_body, .. } => {
+                let before = self.scopes.clone();
+                self.expr(body);
+
+                if let Some(catch_body) = catch_body {
+                    // the body could have stopped anywhere before the catch block runs
+                    let after_body = self.scopes.clone();
+                    self.merge(before);
+                    self.scopes.push(HashMap::new());
+
+                    if let Some(catch_name) = catch_name {
+                        self.declare(catch_name, Variable::Value { ty: Type::Object, annotation: None });
+                    }
+
+                    self.expr(catch_body);
+                    self.scopes.pop();
+                    self.merge(after_body);
+                }
+
+                if let Some(finally_body) = finally_body {
+                    self.expr(finally_body);
+                }
+
+                Type::Any
+            }
+
+            Expr::Match { value, arms } => {
+                self.expr(value);
+
+                let before = self.scopes.clone();
+                let mut ty: Option<Type> = None;
+                let mut after: Option<Scopes> = None;
+
+                for arm in arms {
+                    self.scopes = before.clone();
+                    self.scopes.push(HashMap::new());
+
+                    let mut names = vec![];
+                    bindings(&arm.pattern, &mut names);
+
+                    for name in names {
+                        self.declare(name, Variable::Value { ty: Type::Any, annotation: None });
+                    }
+
+                    if let Some(guard) = &arm.guard {
+                        self.expr(guard);
+                    }
+
+                    let arm_type = self.expr(&arm.body);
+                    self.scopes.pop();
+
+                    if !always_returns(&arm.body) {
+                        ty = Some(match ty {
+                            Some(ty) => join(ty, arm_type),
+                            None => arm_type,
+                        });
+
+                        after = Some(match after {
+                            Some(after) => {
+                                self.merge(after);
+                                self.scopes.clone()
+                            }
+
+                            None => self.scopes.clone(),
+                        });
+                    }
+                }
+
+                self.scopes = after.unwrap_or(before);
+                ty.unwrap_or(Type::Any)
+            }
+
+            Expr::Range { start, end } | Expr::InclusiveRange { start, end } => {
+                self.range_bound(start, "start");
+                self.range_bound(end, "end");
+                Type::Range
+            }
+
+            Expr::Return(value) => {
+                let ty = self.expr(value);
+
+                if let Some(Some(returns)) = self.returns.last().cloned() && !fits(&ty, &returns.node) {
+                    self.mismatch(&ty, &returns, value.span);
+                }
+
+                Type::Any
+            }
+
+            Expr::Break(_) | Expr::Continue(_) => Type::Any,
+        }
+    }
+}
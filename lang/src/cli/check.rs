@@ -0,0 +1,28 @@
+use crate::parser::check as check_file;
+
+pub fn check() {
+    let args = std::env::args().collect::<Vec<String>>();
+
+    let file_path = match args.get(2) {
+        Some(path) => path.clone(),
+        None if std::path::Path::new("main.modu").exists() => "main.modu".to_string(),
+        None => {
+            println!("Usage: modu check [file]");
+            return;
+        }
+    };
+
+    let file = match std::fs::read_to_string(&file_path) {
+        Ok(file) => file,
+        Err(e) => {
+            println!("Could not read {}: {}", file_path, e);
+            std::process::exit(1);
+        }
+    };
+
+    if check_file(&file, &file_path) {
+        println!("No type errors found in {}", file_path);
+    } else {
+        std::process::exit(1);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod run;
+pub mod check;
 pub mod repl;
 pub mod server;
 pub mod login;
@@ -20,7 +20,17 @@ pub fn run() {
         file_path = args[2].clone();
     }
 
-    let context: &mut std::collections::HashMap<String, crate::ast::Expr> = &mut crate::utils::create_context();
+    if let Some(at) = args.iter().position(|arg| arg == "--max-depth") {
+        match args.get(at + 1).and_then(|depth| depth.parse().ok()) {
+            Some(depth) => crate::eval::set_max_depth(depth),
+            None => {
+                println!("Usage: modu run [file] --max-depth <calls>");
+                return;
+            }
+        }
+    }
+
+    let context = crate::utils::create_context();
 
-    parse(&file, &file_path, context);
+    parse(&file, &file_path, &context);
 }
\ No newline at end of file
@@ -52,7 +52,7 @@ pub fn server() {
                     };
                 }
 
-                let context = &mut crate::utils::create_context();
+                let context = &crate::utils::create_context();
 
                 let mut stdout = BufferRedirect::stdout().unwrap();
                 let mut stderr = BufferRedirect::stderr().unwrap();
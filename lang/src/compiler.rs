@@ -0,0 +1,926 @@
+use std::rc::Rc;
+
+use crate::ast::{Arity, Expr, Pattern, Spanned, SpannedExpr};
+use crate::env::Slot;
+use crate::eval::{self, BinaryOp, EvalError, is_assignable};
+use crate::lexer::Span;
+use crate::value::Value;
+
+// lowers the resolved ast into a flat list of instructions for the vm
+// every instruction keeps the span of the node it came from, so errors
+// point at the same place they would with eval
+//
+// every expression leaves exactly one value on the stack, statements in a
+// block pop it again, so the compiler always knows how deep the stack is
+// (needed to clean up when break jumps out of a for loop)
+
+#[derive(Debug)]
+pub enum Op {
+    Push(Value),
+    Pop,
+    PopN(usize),
+    Dup,
+
+    GetLocal(Slot),
+    SetLocal(Slot),
+    GetGlobal(String),
+    // let and fn at the top level, the global is created if needed
+    DefineGlobal(String),
+    // assignment, the global has to exist already
+    SetGlobal(String),
+
+    GetProperty(String),
+    GetIndex,
+    // [value, container] -> [container], the compiler writes the container back after
+    SetProperty(String),
+    // [value, container, index] -> [container], the span is where the index is
+    SetIndex(Span),
+    // [index, ...] -> [index, ..., value], reads a target like a[i].b with the values of its
+    // indexes on the stack, so x[f()] += 1 calls f once, see eval::read_target
+    GetTarget(Rc<SpannedExpr>, usize),
+    // [index, ..., value] -> [], see eval::write_target
+    SetTarget(Rc<SpannedExpr>, usize),
+
+    // [array] -> [rest, ..., second, first], see eval::unpack_array
+    UnpackArray { count: usize, rest: bool },
+    // [object] -> [last, ..., first], the keys keep their spans for missing properties
+    UnpackObject(Rc<[Spanned<String>]>),
+    // literals, ranges and | in a let, errors if the value doesnt match, see eval::destructure
+    Destructure(Rc<Spanned<Pattern>>),
+    // [value] -> [matched], binds the names of the pattern when it matches
+    Match(Rc<Spanned<Pattern>>),
+    // the value on top didnt match any arm
+    NoMatch,
+
+    MakeArray(usize),
+    // joins the values into one string, see eval::interpolate
+    Interpolate(usize),
+    // [key, value, key, value, ...], one span per key for invalid keys
+    MakeObject(Rc<[Span]>),
+    MakeRange { inclusive: bool },
+    MakeFunction(Rc<Prototype>),
+    MakeStruct(Rc<StructPrototype>),
+
+    // the span is the right side, see eval::binary
+    Binary(BinaryOp, Span),
+    Neg,
+    Not,
+    // turns the value into a bool, same rules as if conditions
+    Condition,
+    // pops the condition and jumps if it is false or null
+    JumpIfFalse(usize),
+    Jump(usize),
+    // jumps if the call passed the arg at the index, past the code for its default
+    JumpIfPassed(usize, usize),
+
+    Call(Rc<CallInfo>),
+    // an explicit return statement
+    Return,
+    // the end of a function body or statement, returns the value on top
+    End,
+    // break or continue for a loop that isnt in the current function or statement
+    Unwind { is_break: bool, label: Option<String> },
+
+    // [value] -> [iterator]
+    Iterate,
+    // runs the code until LeaveScope in a new frame of the size, for a for loop that makes closures
+    EnterScope(usize),
+    LeaveScope,
+    // [iterator, index] -> [iterator, index + 1, index, value], jumps when there is nothing left
+    Next(usize),
+
+    Import { name: String, import_as: Option<String> },
+
+    // pops the value and raises it as an error, see eval::throw
+    Throw,
+    // errors from here on jump to the target with the error value pushed, until PopHandler
+    Try(usize),
+    // same, but the error is kept aside and nothing is pushed, Rethrow raises it again after finally
+    TryFinally(usize),
+    PopHandler,
+    Rethrow,
+    // drops the error kept by TryFinally, when break or return leaves a finally block early
+    Discard,
+}
+
+#[derive(Debug, Default)]
+pub struct Chunk {
+    pub code: Vec<Op>,
+    pub spans: Vec<Span>,
+}
+
+// what MakeFunction needs to create a function, the body is compiled up front
+#[derive(Debug)]
+pub struct Prototype {
+    pub name: String,
+    pub args: Vec<String>,
+    pub arity: Arity,
+    pub body: Rc<SpannedExpr>,
+    pub frame_size: usize,
+    pub code: Rc<Chunk>,
+}
+
+#[derive(Debug)]
+pub struct StructPrototype {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub methods: Vec<(String, Prototype)>,
+}
+
+#[derive(Debug)]
+pub struct CallInfo {
+    pub arg_spans: Vec<Span>,
+    // f(x, timeout: 5), the names of the last args, the values are with the others
+    pub named: Vec<Spanned<String>>,
+    pub receiver: Option<Receiver>,
+    // return f(x) outside of try blocks, a modu function replaces the current frame
+    pub tail: bool,
+}
+
+// for obj.method(), the object sits below the function on the stack
+#[derive(Debug)]
+pub struct Receiver {
+    pub name: String,
+    pub span: Span,
+    // if the object can be assigned to, the call pushes what the function
+    // replaced self with and a bool, so arr.push(1) can write arr back
+    pub write_back: bool,
+}
+
+struct Loop {
+    label: Option<String>,
+    depth: usize,
+    // how many regions were open when the loop started
+    regions: usize,
+    continue_target: usize,
+    breaks: Vec<usize>,
+}
+
+// a try or catch block the code being compiled is inside of, anything that jumps
+// out of it has to remove its handler and run the finally block on the way
+enum Region<'a> {
+    Handler {
+        finally: Option<&'a SpannedExpr>,
+        // how many loops were open when the region started
+        loops: usize,
+    },
+    // the finally block that runs before an error is thrown again
+    Rethrow,
+    // the body of a for loop that runs in its own frame, see Op::EnterScope
+    Scope,
+}
+
+struct Compiler<'a> {
+    chunk: Chunk,
+    depth: usize,
+    loops: Vec<Loop>,
+    regions: Vec<Region<'a>>,
+}
+
+// compiles a top level statement or a function body, the arguments of a
+// function are already in the first slots of its frame when the body runs
+pub fn compile(expr: &SpannedExpr) -> Result<Chunk, EvalError> {
+    let mut compiler = Compiler { chunk: Chunk::default(), depth: 0, loops: vec![], regions: vec![] };
+
+    compiler.expr(expr)?;
+    compiler.emit(Op::End, expr.span);
+
+    Ok(compiler.chunk)
+}
+
+impl<'a> Compiler<'a> {
+    fn emit(&mut self, op: Op, span: Span) -> usize {
+        let effect: isize = match &op {
+            Op::Push(_) | Op::Dup | Op::GetLocal(_) | Op::GetGlobal(_) | Op::MakeFunction(_) | Op::MakeStruct(_) | Op::Import { .. } => 1,
+            Op::Pop | Op::SetLocal(_) | Op::DefineGlobal(_) | Op::SetGlobal(_) | Op::JumpIfFalse(_) | Op::Throw => -1,
+            Op::Destructure(_) => -1,
+            Op::GetIndex | Op::SetProperty(_) | Op::MakeRange { .. } | Op::Binary(..) | Op::Return | Op::End => -1,
+            Op::SetIndex(_) => -2,
+            Op::GetTarget(..) => 1,
+            Op::SetTarget(_, count) => -(*count as isize) - 1,
+            Op::PopN(n) => -(*n as isize),
+            Op::MakeArray(n) | Op::Interpolate(n) => 1 - *n as isize,
+            Op::MakeObject(keys) => 1 - 2 * keys.len() as isize,
+            Op::UnpackArray { count, rest } => *count as isize + *rest as isize - 1,
+            Op::UnpackObject(keys) => keys.len() as isize - 1,
+            Op::Next(_) => 2,
+            Op::Call(info) => {
+                let popped = info.arg_spans.len() + 1 + info.receiver.is_some() as usize;
+                let pushed = match &info.receiver {
+                    Some(receiver) if receiver.write_back => 3,
+                    _ => 1,
+                };
+
+                pushed - popped as isize
+            }
+            Op::GetProperty(_) | Op::Neg | Op::Not | Op::Condition | Op::Jump(_) | Op::JumpIfPassed(..) | Op::Unwind { .. } | Op::Iterate => 0,
+            Op::Try(_) | Op::TryFinally(_) | Op::PopHandler | Op::Rethrow | Op::Discard => 0,
+            Op::Match(_) | Op::NoMatch | Op::EnterScope(_) | Op::LeaveScope => 0,
+        };
+
+        self.depth = (self.depth as isize + effect) as usize;
+
+        self.chunk.code.push(op);
+        self.chunk.spans.push(span);
+        self.chunk.code.len() - 1
+    }
+
+    fn here(&self) -> usize {
+        self.chunk.code.len()
+    }
+
+    // points an already emitted jump at the current position
+    fn patch(&mut self, at: usize) {
+        let target = self.here();
+
+        match &mut self.chunk.code[at] {
+            Op::Jump(to) | Op::JumpIfFalse(to) | Op::JumpIfPassed(_, to) | Op::Next(to) | Op::Try(to) | Op::TryFinally(to) => *to = target,
+            op => unreachable!("cannot patch {:?}", op),
+        }
+    }
+
+    fn exprs(&mut self, exprs: &'a [SpannedExpr]) -> Result<(), EvalError> {
+        for expr in exprs {
+            self.expr(expr)?;
+        }
+
+        Ok(())
+    }
+
+    // stores the value on top of the stack into a variable, let and fn
+    fn define(&mut self, name: &str, slot: &Option<Slot>, span: Span) {
+        match slot {
+            Some(slot) => self.emit(Op::SetLocal(*slot), span),
+            None => self.emit(Op::DefineGlobal(name.to_string()), span),
+        };
+    }
+
+    // takes the value on top of the stack apart, see eval::destructure
+    // the parts are pushed so the first one is on top, each nested pattern pops its own
+    fn destructure(&mut self, pattern: &Spanned<Pattern>) {
+        match &pattern.node {
+            Pattern::Wildcard => { self.emit(Op::Pop, pattern.span); }
+
+            Pattern::Binding { name, slot } => self.define(name, slot, pattern.span),
+
+            Pattern::Literal(_) | Pattern::Range { .. } | Pattern::Or(_) | Pattern::Variant { .. } => {
+                self.emit(Op::Destructure(Rc::new(pattern.clone())), pattern.span);
+            }
+
+            Pattern::Array { elements, rest } => {
+                self.emit(Op::UnpackArray { count: elements.len(), rest: rest.is_some() }, pattern.span);
+
+                for element in elements.iter().chain(rest.as_deref()) {
+                    self.destructure(element);
+                }
+            }
+
+            Pattern::Object { properties } => {
+                let keys = properties.iter().map(|(key, _)| key.clone()).collect();
+                self.emit(Op::UnpackObject(keys), pattern.span);
+
+                for (_, property) in properties {
+                    self.destructure(property);
+                }
+            }
+        }
+    }
+
+    // stores the value on top of the stack into an assignment target, see eval::assign
+    fn store(&mut self, target: &'a SpannedExpr) -> Result<(), EvalError> {
+        match &target.node {
+            Expr::Identifier(name) => {
+                self.emit(Op::SetGlobal(name.clone()), target.span);
+            }
+
+            Expr::Local { slot } => {
+                self.emit(Op::SetLocal(*slot), target.span);
+            }
+
+            Expr::PropertyAccess { object, property } => {
+                self.expr(object)?;
+                self.emit(Op::SetProperty(property.clone()), object.span);
+                self.store(object)?;
+            }
+
+            Expr::IndexAccess { object, index } => {
+                self.expr(object)?;
+                self.expr(index)?;
+                self.emit(Op::SetIndex(index.span), object.span);
+                self.store(object)?;
+            }
+
+            _ => return Err(eval::invalid_target(target.span)),
+        }
+
+        Ok(())
+    }
+
+    // pushes the indexes of a target like a[i].b[j], outermost first, and returns how many
+    fn target_indexes(&mut self, target: &'a SpannedExpr) -> Result<usize, EvalError> {
+        match &target.node {
+            Expr::Identifier(_) | Expr::Local { .. } => Ok(0),
+            Expr::PropertyAccess { object, .. } => self.target_indexes(object),
+
+            Expr::IndexAccess { object, index } => {
+                let count = self.target_indexes(object)?;
+                self.expr(index)?;
+
+                Ok(count + 1)
+            }
+
+            _ => Err(eval::invalid_target(target.span)),
+        }
+    }
+
+    fn prototype(name: &str, args: &[String], arity: Arity, body: &Rc<SpannedExpr>, frame_size: usize) -> Result<Prototype, EvalError> {
+        Ok(Prototype {
+            name: name.to_string(),
+            args: args.to_vec(),
+            arity,
+            body: body.clone(),
+            frame_size,
+            code: Rc::new(compile(body)?),
+        })
+    }
+
+    fn function(&mut self, name: &str, args: &[String], arity: Arity, body: &Rc<SpannedExpr>, frame_size: usize, span: Span) -> Result<(), EvalError> {
+        let prototype = Self::prototype(name, args, arity, body, frame_size)?;
+        self.emit(Op::MakeFunction(Rc::new(prototype)), span);
+
+        Ok(())
+    }
+
+    fn binary(&mut self, op: BinaryOp, expr: &SpannedExpr, left: &'a SpannedExpr, right: &'a SpannedExpr) -> Result<(), EvalError> {
+        self.expr(left)?;
+        self.expr(right)?;
+        self.emit(Op::Binary(op, right.span), expr.span);
+
+        Ok(())
+    }
+
+    fn call(&mut self, expr: &SpannedExpr, callee: &'a SpannedExpr, args: &'a [SpannedExpr], named: &'a [(Spanned<String>, SpannedExpr)], tail: bool) -> Result<(), EvalError> {
+        let receiver = match &callee.node {
+            Expr::PropertyAccess { object, property } => {
+                self.expr(object)?;
+                self.emit(Op::Dup, object.span);
+                self.emit(Op::GetProperty(property.clone()), callee.span);
+
+                Some(Receiver {
+                    name: property.clone(),
+                    span: object.span,
+                    write_back: is_assignable(object),
+                })
+            }
+
+            _ => {
+                self.expr(callee)?;
+                None
+            }
+        };
+
+        self.exprs(args)?;
+
+        for (_, arg) in named {
+            self.expr(arg)?;
+        }
+
+        let write_back = receiver.as_ref().is_some_and(|receiver| receiver.write_back);
+
+        self.emit(Op::Call(Rc::new(CallInfo {
+            arg_spans: args.iter().chain(named.iter().map(|(_, arg)| arg)).map(|arg| arg.span).collect(),
+            named: named.iter().map(|(name, _)| name.clone()).collect(),
+            receiver,
+            tail,
+        })), expr.span);
+
+        if write_back && let Expr::PropertyAccess { object, .. } = &callee.node {
+            let skip = self.emit(Op::JumpIfFalse(0), object.span);
+            self.store(object)?;
+            self.patch(skip);
+        }
+
+        Ok(())
+    }
+
+    // leaves the regions above the given count, innermost first, used before return and break
+    fn leave_regions(&mut self, count: usize, span: Span) -> Result<(), EvalError> {
+        for index in (count..self.regions.len()).rev() {
+            match self.regions[index] {
+                Region::Handler { finally, loops } => {
+                    self.emit(Op::PopHandler, span);
+
+                    if let Some(finally) = finally {
+                        // the finally block itself is outside the region and the loops inside it
+                        let regions = self.regions.split_off(index);
+                        let inner_loops = self.loops.split_off(loops);
+
+                        self.expr(finally)?;
+                        self.emit(Op::Pop, finally.span);
+
+                        self.loops.extend(inner_loops);
+                        self.regions.extend(regions);
+                    }
+                }
+
+                Region::Rethrow => { self.emit(Op::Discard, span); }
+                Region::Scope => { self.emit(Op::LeaveScope, span); }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn jump_out(&mut self, is_break: bool, label: &Option<String>, span: Span) -> Result<(), EvalError> {
+        let depth = self.depth;
+
+        let found = self.loops.iter().rposition(|l| label.is_none() || l.label == *label);
+
+        match found {
+            Some(index) => {
+                self.leave_regions(self.loops[index].regions, span)?;
+
+                let extra = self.depth - self.loops[index].depth;
+
+                if extra > 0 {
+                    self.emit(Op::PopN(extra), span);
+                }
+
+                if is_break {
+                    let jump = self.emit(Op::Jump(0), span);
+                    self.loops[index].breaks.push(jump);
+                } else {
+                    let target = self.loops[index].continue_target;
+                    self.emit(Op::Jump(target), span);
+                }
+            }
+
+            None => {
+                self.leave_regions(0, span)?;
+                self.emit(Op::Unwind { is_break, label: label.clone() }, span);
+            }
+        }
+
+        // nothing after this runs, but the statement still counts as a value
+        self.depth = depth + 1;
+
+        Ok(())
+    }
+
+    fn begin_loop(&mut self, label: &Option<String>, continue_target: usize) {
+        self.loops.push(Loop {
+            label: label.clone(),
+            depth: self.depth,
+            regions: self.regions.len(),
+            continue_target,
+            breaks: vec![],
+        });
+    }
+
+    fn end_loop(&mut self) {
+        let finished = self.loops.pop().unwrap();
+
+        for jump in finished.breaks {
+            self.patch(jump);
+        }
+    }
+
+    // try { body } catch e { catch_body } finally { finally_body } becomes
+    //
+    //     Try catch          (TryFinally rethrow without a catch block)
+    //     body, Pop, PopHandler, Jump normal
+    //   catch:
+    //     store e, TryFinally rethrow
+    //     catch_body, Pop, PopHandler, Jump normal
+    //   rethrow:
+    //     finally_body, Pop, Rethrow
+    //   normal:
+    //     finally_body, Pop, Push null
+    //
+    // without a finally block the catch block has no handler and both labels are the end
+    fn try_catch(&mut self, body: &'a SpannedExpr, catch_slot: Option<Slot>, catch_body: Option<&'a SpannedExpr>, finally_body: Option<&'a SpannedExpr>, span: Span) -> Result<(), EvalError> {
+        let depth = self.depth;
+        let mut to_normal = vec![];
+        let mut to_rethrow = vec![];
+
+        let handler = match catch_body {
+            Some(_) => self.emit(Op::Try(0), span),
+            None => self.emit(Op::TryFinally(0), span),
+        };
+
+        self.guarded(body, finally_body)?;
+        self.emit(Op::PopHandler, span);
+        to_normal.push(self.emit(Op::Jump(0), span));
+
+        match catch_body {
+            Some(catch_body) => {
+                self.patch(handler);
+                self.depth = depth + 1;
+
+                match catch_slot {
+                    Some(slot) => self.emit(Op::SetLocal(slot), span),
+                    None => self.emit(Op::Pop, span),
+                };
+
+                match finally_body {
+                    Some(_) => {
+                        to_rethrow.push(self.emit(Op::TryFinally(0), span));
+                        self.guarded(catch_body, finally_body)?;
+                        self.emit(Op::PopHandler, span);
+                        to_normal.push(self.emit(Op::Jump(0), span));
+                    }
+
+                    None => {
+                        self.expr(catch_body)?;
+                        self.emit(Op::Pop, catch_body.span);
+                    }
+                }
+            }
+
+            None => to_rethrow.push(handler),
+        }
+
+        if let Some(finally_body) = finally_body {
+            for jump in to_rethrow {
+                self.patch(jump);
+            }
+
+            self.depth = depth;
+            self.regions.push(Region::Rethrow);
+            self.expr(finally_body)?;
+            self.regions.pop();
+            self.emit(Op::Pop, finally_body.span);
+            self.emit(Op::Rethrow, span);
+        }
+
+        self.depth = depth;
+
+        for jump in to_normal {
+            self.patch(jump);
+        }
+
+        if let Some(finally_body) = finally_body {
+            self.expr(finally_body)?;
+            self.emit(Op::Pop, finally_body.span);
+        }
+
+        self.emit(Op::Push(Value::Null), span);
+
+        Ok(())
+    }
+
+    // compiles a block that has a handler installed around it
+    fn guarded(&mut self, body: &'a SpannedExpr, finally: Option<&'a SpannedExpr>) -> Result<(), EvalError> {
+        self.regions.push(Region::Handler { finally, loops: self.loops.len() });
+        self.expr(body)?;
+        self.regions.pop();
+        self.emit(Op::Pop, body.span);
+
+        Ok(())
+    }
+
+    fn expr(&mut self, expr: &'a SpannedExpr) -> Result<(), EvalError> {
+        let span = expr.span;
+
+        match &expr.node {
+            Expr::Int(n) => { self.emit(Op::Push(Value::Int(*n)), span); }
+            Expr::Float(f) => { self.emit(Op::Push(Value::Float(*f)), span); }
+            Expr::String(s) => { self.emit(Op::Push(Value::String(s.as_str().into())), span); }
+            Expr::Bool(b) => { self.emit(Op::Push(Value::Bool(*b)), span); }
+            Expr::Null => { self.emit(Op::Push(Value::Null), span); }
+
+            Expr::Identifier(name) => { self.emit(Op::GetGlobal(name.clone()), span); }
+            Expr::Local { slot } => { self.emit(Op::GetLocal(*slot), span); }
+
+            Expr::PropertyAccess { object, property } => {
+                self.expr(object)?;
+                self.emit(Op::GetProperty(property.clone()), span);
+            }
+
+            Expr::IndexAccess { object, index } => {
+                self.expr(object)?;
+                self.expr(index)?;
+                self.emit(Op::GetIndex, span);
+            }
+
+            Expr::Neg(inner) => {
+                self.expr(inner)?;
+                self.emit(Op::Neg, span);
+            }
+
+            Expr::Add(left, right) => self.binary(BinaryOp::Add, expr, left, right)?,
+            Expr::Sub(left, right) => self.binary(BinaryOp::Sub, expr, left, right)?,
+            Expr::Mul(left, right) => self.binary(BinaryOp::Mul, expr, left, right)?,
+            Expr::Div(left, right) => self.binary(BinaryOp::Div, expr, left, right)?,
+            Expr::Mod(left, right) => self.binary(BinaryOp::Mod, expr, left, right)?,
+            Expr::Equal(left, right) => self.binary(BinaryOp::Equal, expr, left, right)?,
+            Expr::NotEqual(left, right) => self.binary(BinaryOp::NotEqual, expr, left, right)?,
+            Expr::LessThan(left, right) => self.binary(BinaryOp::LessThan, expr, left, right)?,
+            Expr::LessThanOrEqual(left, right) => self.binary(BinaryOp::LessThanOrEqual, expr, left, right)?,
+            Expr::GreaterThan(left, right) => self.binary(BinaryOp::GreaterThan, expr, left, right)?,
+            Expr::GreaterThanOrEqual(left, right) => self.binary(BinaryOp::GreaterThanOrEqual, expr, left, right)?,
+
+            Expr::And(left, right) => {
+                self.expr(left)?;
+                let short_circuit = self.emit(Op::JumpIfFalse(0), left.span);
+                self.expr(right)?;
+                self.emit(Op::Condition, right.span);
+                let end = self.emit(Op::Jump(0), span);
+
+                self.depth -= 1;
+                self.patch(short_circuit);
+                self.emit(Op::Push(Value::Bool(false)), span);
+                self.patch(end);
+            }
+
+            Expr::Or(left, right) => {
+                self.expr(left)?;
+                let check_right = self.emit(Op::JumpIfFalse(0), left.span);
+                self.emit(Op::Push(Value::Bool(true)), span);
+                let end = self.emit(Op::Jump(0), span);
+
+                self.depth -= 1;
+                self.patch(check_right);
+                self.expr(right)?;
+                self.emit(Op::Condition, right.span);
+                self.patch(end);
+            }
+
+            Expr::Not(inner) => {
+                self.expr(inner)?;
+                self.emit(Op::Not, inner.span);
+            }
+
+            Expr::Range { start, end } | Expr::InclusiveRange { start, end } => {
+                self.expr(start)?;
+                self.expr(end)?;
+                self.emit(Op::MakeRange { inclusive: matches!(expr.node, Expr::InclusiveRange { .. }) }, span);
+            }
+
+            Expr::Array(elements) => {
+                self.exprs(elements)?;
+                self.emit(Op::MakeArray(elements.len()), span);
+            }
+
+            Expr::Interpolation(parts) => {
+                self.exprs(parts)?;
+                self.emit(Op::Interpolate(parts.len()), span);
+            }
+
+            Expr::ObjectLiteral { properties } => {
+                for (key, value) in properties {
+                    self.expr(key)?;
+                    self.expr(value)?;
+                }
+
+                let key_spans = properties.iter().map(|(key, _)| key.span).collect();
+                self.emit(Op::MakeObject(key_spans), span);
+            }
+
+            Expr::Call { callee, args, named } => self.call(expr, callee, args, named, false)?,
+
+            Expr::Let { name, value, slot, .. } => {
+                self.expr(value)?;
+                self.define(name, slot, span);
+                self.emit(Op::Push(Value::Null), span);
+            }
+
+            Expr::Destructure { pattern, value } => {
+                self.expr(value)?;
+                self.destructure(pattern);
+                self.emit(Op::Push(Value::Null), span);
+            }
+
+            // every arm starts with the value on the stack and only pops it once it is taken
+            Expr::Match { value, arms } => {
+                self.expr(value)?;
+
+                let mut to_end = vec![];
+
+                for arm in arms {
+                    self.emit(Op::Dup, arm.pattern.span);
+                    self.emit(Op::Match(Rc::new(arm.pattern.clone())), arm.pattern.span);
+
+                    let mut to_next = vec![self.emit(Op::JumpIfFalse(0), arm.pattern.span)];
+
+                    if let Some(guard) = &arm.guard {
+                        self.expr(guard)?;
+                        to_next.push(self.emit(Op::JumpIfFalse(0), guard.span));
+                    }
+
+                    self.emit(Op::Pop, span);
+                    self.expr(&arm.body)?;
+                    to_end.push(self.emit(Op::Jump(0), span));
+
+                    for jump in to_next {
+                        self.patch(jump);
+                    }
+                }
+
+                self.emit(Op::NoMatch, value.span);
+
+                for jump in to_end {
+                    self.patch(jump);
+                }
+            }
+
+            Expr::Assign { target, value } => {
+                self.expr(value)?;
+                self.store(target)?;
+                self.emit(Op::Push(Value::Null), span);
+            }
+
+            Expr::CompoundAssign { op, target, value } => {
+                let operation = Span::from(target.span.start..value.span.end);
+
+                match &target.node {
+                    Expr::Identifier(_) | Expr::Local { .. } => {
+                        self.expr(target)?;
+                        self.expr(value)?;
+                        self.emit(Op::Binary(*op, value.span), operation);
+                        self.store(target)?;
+                    }
+
+                    _ => {
+                        let count = self.target_indexes(target)?;
+                        let target = Rc::new((**target).clone());
+
+                        self.emit(Op::GetTarget(target.clone(), count), target.span);
+                        self.expr(value)?;
+                        self.emit(Op::Binary(*op, value.span), operation);
+                        self.emit(Op::SetTarget(target, count), span);
+                    }
+                }
+
+                self.emit(Op::Push(Value::Null), span);
+            }
+
+            Expr::Function { name, args, arity, body, slot, frame_size, .. } => {
+                self.function(name, args, *arity, body, *frame_size, span)?;
+                self.define(name, slot, span);
+                self.emit(Op::Push(Value::Null), span);
+            }
+
+            Expr::Struct { name, fields, methods, slot } => {
+                let methods = methods.iter()
+                    .map(|method| {
+                        let prototype = Self::prototype(&format!("{}.{}", name, method.name), &method.args, method.arity, &method.body, method.frame_size)?;
+                        Ok((method.name.clone(), prototype))
+                    })
+                    .collect::<Result<Vec<_>, EvalError>>()?;
+
+                let prototype = StructPrototype { name: name.clone(), fields: fields.clone(), methods };
+
+                self.emit(Op::MakeStruct(Rc::new(prototype)), span);
+                self.define(name, slot, span);
+                self.emit(Op::Push(Value::Null), span);
+            }
+
+            Expr::Enum { name, variants, slot } => {
+                self.emit(Op::Push(eval::make_enum(name, variants)), span);
+                self.define(name, slot, span);
+                self.emit(Op::Push(Value::Null), span);
+            }
+
+            Expr::Lambda { args, arity, body, frame_size, .. } => {
+                self.function("<anonymous>", args, *arity, body, *frame_size, span)?;
+            }
+
+            Expr::Default { index, value, .. } => {
+                let passed = self.emit(Op::JumpIfPassed(*index, 0), span);
+                self.expr(value)?;
+                self.emit(Op::SetLocal(Slot { depth: 0, index: *index }), span);
+                self.patch(passed);
+                self.emit(Op::Push(Value::Null), span);
+            }
+
+            Expr::Block { statements, value } => {
+                for e in statements {
+                    self.expr(e)?;
+                    self.emit(Op::Pop, e.span);
+                }
+
+                match value {
+                    Some(value) => self.expr(value)?,
+                    None => { self.emit(Op::Push(Value::Null), span); }
+                }
+            }
+
+            Expr::If { condition, then_branch, else_branch } => {
+                self.expr(condition)?;
+                let to_else = self.emit(Op::JumpIfFalse(0), span);
+                self.expr(then_branch)?;
+                let to_end = self.emit(Op::Jump(0), span);
+
+                self.depth -= 1;
+                self.patch(to_else);
+
+                match else_branch {
+                    Some(else_branch) => self.expr(else_branch)?,
+                    None => { self.emit(Op::Push(Value::Null), span); }
+                }
+
+                self.patch(to_end);
+            }
+
+            Expr::InfiniteLoop { body, label } => {
+                let start = self.here();
+                self.begin_loop(label, start);
+
+                self.expr(body)?;
+                self.emit(Op::Pop, body.span);
+                self.emit(Op::Jump(start), span);
+
+                self.end_loop();
+                self.emit(Op::Push(Value::Null), span);
+            }
+
+            Expr::WhileLoop { condition, body, label } => {
+                let start = self.here();
+                self.begin_loop(label, start);
+
+                self.expr(condition)?;
+                let exit = self.emit(Op::JumpIfFalse(0), condition.span);
+                self.expr(body)?;
+                self.emit(Op::Pop, body.span);
+                self.emit(Op::Jump(start), span);
+
+                self.patch(exit);
+                self.end_loop();
+                self.emit(Op::Push(Value::Null), span);
+            }
+
+            Expr::ForLoop { iterator_name, index_name, iterator_slot, index_slot, iterator_range, body, label, scope_size } => {
+                self.expr(iterator_range)?;
+                self.emit(Op::Iterate, iterator_range.span);
+                self.emit(Op::Push(Value::Int(0)), span);
+
+                let next = self.here();
+                self.begin_loop(label, next);
+                self.emit(Op::Next(0), iterator_range.span);
+
+                if let Some(size) = scope_size {
+                    self.emit(Op::EnterScope(*size), span);
+                    self.regions.push(Region::Scope);
+                }
+
+                self.define(iterator_name, iterator_slot, span);
+
+                match index_name {
+                    Some(index_name) => self.define(index_name, index_slot, span),
+                    None => { self.emit(Op::Pop, span); }
+                }
+
+                self.expr(body)?;
+                self.emit(Op::Pop, body.span);
+
+                if scope_size.is_some() {
+                    self.regions.pop();
+                    self.emit(Op::LeaveScope, span);
+                }
+
+                self.emit(Op::Jump(next), span);
+
+                // the stack is back to [iterator, index] here, both when the
+                // iterator runs out and when break jumps out
+                self.patch(next);
+                self.end_loop();
+                self.emit(Op::PopN(2), span);
+                self.emit(Op::Push(Value::Null), span);
+            }
+
+            Expr::Return(value) => {
+                // a finally block has to run after the call, so it cant replace the frame
+                match &value.node {
+                    Expr::Call { callee, args, named } if self.regions.iter().all(|region| matches!(region, Region::Scope)) => {
+                        self.call(value, callee, args, named, true)?
+                    }
+                    _ => self.expr(value)?,
+                }
+
+                self.leave_regions(0, span)?;
+                self.emit(Op::Return, span);
+                self.depth += 1;
+            }
+
+            Expr::Break(label) => self.jump_out(true, label, span)?,
+            Expr::Continue(label) => self.jump_out(false, label, span)?,
+
+            Expr::Throw(value) => {
+                self.expr(value)?;
+                self.emit(Op::Throw, span);
+                self.depth += 1;
+            }
+
+            Expr::Try { body, catch_slot, catch_body, finally_body, .. } => {
+                self.try_catch(body, *catch_slot, catch_body.as_deref(), finally_body.as_deref(), span)?;
+            }
+
+            Expr::Import { name, import_as } => {
+                self.emit(Op::Import { name: name.clone(), import_as: import_as.clone() }, span);
+            }
+        }
+
+        Ok(())
+    }
+}
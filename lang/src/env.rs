@@ -0,0 +1,149 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use rustc_hash::FxHashMap;
+
+use crate::value::Value;
+
+// top-level variables of a script or module, looked up by name
+// FxHashMap since a loop over globals hashes the name on every read and write,
+// and the default hasher is several times slower for short keys like these
+pub type Globals = Rc<RefCell<FxHashMap<String, Value>>>;
+
+// where a local variable lives, found once by the resolver before running
+// depth is how many frames up from the current one, index is the slot in that frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Slot {
+    pub depth: usize,
+    pub index: usize,
+}
+
+// one per function call, plus one for the script itself
+// parent is the frame the function was defined in, not the one it was called from
+pub struct Frame {
+    // Cells instead of a RefCell around all of them, so reading or writing a slot
+    // doesnt have to track a borrow, see get
+    slots: Box<[Cell<Value>]>,
+    parent: Option<Rc<Frame>>,
+    // how many arguments the call passed, the args after that get their defaults
+    // and so do the skipped ones, that named args jumped over
+    passed: usize,
+    skipped: Vec<usize>,
+}
+
+// the file a script or module was loaded from, so errors from functions
+// defined in an imported module can be reported against that module
+#[derive(Debug)]
+pub struct Source {
+    pub name: String,
+    pub text: String,
+}
+
+impl Source {
+    // 1-based line and column of a byte offset into the text
+    pub fn location(&self, offset: usize) -> (usize, usize) {
+        let before = &self.text[..offset.min(self.text.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+
+        (line, column)
+    }
+}
+
+#[derive(Clone)]
+pub struct Env {
+    pub globals: Globals,
+    pub frame: Rc<Frame>,
+    pub source: Rc<Source>,
+}
+
+impl Env {
+    pub fn new(globals: Globals, size: usize, source: Rc<Source>) -> Self {
+        Env {
+            globals,
+            source,
+            frame: Rc::new(Frame {
+                slots: empty_slots(size),
+                parent: None,
+                passed: 0,
+                skipped: vec![],
+            }),
+        }
+    }
+
+    // the environment a function body runs in, its parent is the frame the function captured
+    pub fn call(&self, size: usize, passed: usize, skipped: Vec<usize>) -> Self {
+        Env {
+            globals: self.globals.clone(),
+            source: self.source.clone(),
+            frame: Rc::new(Frame {
+                slots: empty_slots(size),
+                parent: Some(self.frame.clone()),
+                passed,
+                skipped,
+            }),
+        }
+    }
+
+    // back to the frame of the enclosing function, after a for loop iteration that had its own
+    pub fn parent(&self) -> Self {
+        Env {
+            globals: self.globals.clone(),
+            source: self.source.clone(),
+            frame: self.frame.parent.clone().expect("a scope always has the frame it was entered from"),
+        }
+    }
+
+    pub fn was_passed(&self, index: usize) -> bool {
+        index < self.frame.passed && !self.frame.skipped.contains(&index)
+    }
+
+    fn frame_at(&self, depth: usize) -> &Frame {
+        let mut frame = &*self.frame;
+
+        for _ in 0..depth {
+            frame = frame.parent.as_deref().expect("slot depth goes past the outermost frame");
+        }
+
+        frame
+    }
+
+    pub fn get(&self, slot: Slot) -> Value {
+        let cell = &self.frame_at(slot.depth).slots[slot.index];
+
+        // SAFETY: cloning a value only bumps a reference count and never reaches back
+        // into an env, so nothing can set this slot while the reference is alive
+        unsafe { (*cell.as_ptr()).clone() }
+    }
+
+    pub fn set(&self, slot: Slot, value: Value) {
+        self.frame_at(slot.depth).slots[slot.index].set(value);
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.globals.borrow().get(name).cloned()
+    }
+
+    pub fn set_global(&self, name: &str, value: Value) {
+        let mut globals = self.globals.borrow_mut();
+
+        match globals.get_mut(name) {
+            Some(existing) => *existing = value,
+            None => { globals.insert(name.to_string(), value); }
+        }
+    }
+
+    pub fn has_global(&self, name: &str) -> bool {
+        self.globals.borrow().contains_key(name)
+    }
+}
+
+fn empty_slots(size: usize) -> Box<[Cell<Value>]> {
+    (0..size).map(|_| Cell::new(Value::Null)).collect()
+}
+
+impl std::fmt::Debug for Env {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Env")
+    }
+}
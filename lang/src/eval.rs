@@ -1,832 +1,2208 @@
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::rc::Rc;
 use chumsky::span::SimpleSpan;
 
-use crate::ast::{Expr, SpannedExpr};
+use crate::ast::{Arity, Expr, Pattern, Spanned, SpannedExpr};
+use crate::env::{Env, Slot, Source};
 use crate::lexer::Span;
+use crate::value::{Enum, Function, Instance, InternalFunctionResponse, Struct, Value, ValueIterator, Variant};
 
 #[derive(Debug)]
 pub struct EvalError {
     pub message: String,
     pub message_short: String,
     pub span: Span,
+    // the file the span is in, filled in when the error leaves the function it happened in
+    // None means the file currently being run
+    pub source: Option<Rc<Source>>,
+    // the calls the error passed through on its way out, innermost first
+    pub trace: Vec<TraceFrame>,
+    // the value of a throw, handed to the catch block, None for errors modu made itself
+    pub thrown: Option<Value>,
 }
 
+#[derive(Debug, Clone)]
+pub struct TraceFrame {
+    pub function: String,
+    // the call itself, in the file of the caller
+    pub span: Span,
+    pub source: Rc<Source>,
+}
+
+// f(x, timeout: 5) once the values are evaluated, the name of each with its value
+pub type NamedArgs = Vec<(Spanned<String>, Spanned<Value>)>;
+
 #[derive(Debug)]
 pub enum Flow {
-    Continue(Expr),
-    Return(Expr),
-    Break,
-    Skip,
+    Continue(Value),
+    Return(Value),
+    Break(Option<String>), // label of the loop to break out of, None for the innermost
+    Skip(Option<String>),
+    // return f(x), the function that is returning makes the call, see run_function
+    // boxed since its much bigger than the other variants and every node returns a Flow
+    TailCall(Box<TailCall>),
+}
+
+#[derive(Debug)]
+pub struct TailCall {
+    pub function: Rc<Function>,
+    pub args: Vec<Spanned<Value>>,
+    pub skipped: Vec<usize>,
+    pub span: Span,
+}
+
+// the value of an expression inside another one, a return or break in an if or
+// match used as a value leaves the outer expression with it
+macro_rules! value {
+    ($flow:expr) => {
+        match $flow {
+            Flow::Continue(value) => value,
+            flow => return Ok(flow),
+        }
+    };
+}
+
+// like value!(eval(...)?) but reads ints and variables in place, so the operands
+// of i < n or total += i dont each go through eval and back
+macro_rules! operand {
+    ($expr:expr, $env:expr) => {
+        match &$expr.node {
+            Expr::Int(n) => Value::Int(*n),
+            Expr::Local { slot } => $env.get(*slot),
+            // an undefined one goes through eval for its error
+            Expr::Identifier(name) => match $env.get_global(name) {
+                Some(value) => value,
+                None => value!(eval($expr, $env)?),
+            },
+            _ => value!(eval($expr, $env)?),
+        }
+    };
 }
 
 impl Flow {
-    fn unwrap(self) -> Expr {
+    fn unwrap(self) -> Value {
         match self {
             Flow::Continue(v) | Flow::Return(v) => v,
-            Flow::Break | Flow::Skip => Expr::Null,
+            Flow::Break(_) | Flow::Skip(_) | Flow::TailCall(_) => Value::Null,
+        }
+    }
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+// eval and the calls it makes return the error boxed, so the result passed back up
+// through every node stays small, the helpers that only work on values dont
+impl From<Box<EvalError>> for EvalError {
+    fn from(error: Box<EvalError>) -> Self {
+        *error
+    }
+}
+
+// how deep modu function calls can nest, past this calling another function
+// is an error, change it with modu run --max-depth or set_max_depth
+pub const DEFAULT_MAX_DEPTH: usize = 10_000;
+
+// one call needs a lot less than this in release builds, debug builds need around 100kb
+const STACK_RED_ZONE: usize = 256 * 1024;
+const STACK_GROW_SIZE: usize = 4 * 1024 * 1024;
+
+thread_local! {
+    static MAX_DEPTH: Cell<usize> = const { Cell::new(DEFAULT_MAX_DEPTH) };
+    // how many calls eval is inside of right now
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+// for embedding modu, the limit applies to both eval and the vm
+pub fn set_max_depth(depth: usize) {
+    MAX_DEPTH.set(depth);
+}
+
+pub fn max_depth() -> usize {
+    MAX_DEPTH.get()
+}
+
+pub fn too_deep(span: Span) -> EvalError {
+    EvalError {
+        message: format!("Maximum call depth of {} exceeded, the recursion is too deep or never ends", max_depth()),
+        message_short: "too much recursion".to_string(),
+        span,
+        source: None,
+        trace: vec![],
+        thrown: None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Equal,
+    NotEqual,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+}
+
+// everything below up to eval works on values only, so the vm can share it
+// and both give the same results and errors
+
+// throw "oops"; uses the value as the message, throwing an object with a message
+// field (like a caught error) keeps its message and message_short
+// the value itself is kept for the catch block, see error_value
+pub fn throw(value: Value, span: Span) -> EvalError {
+    let field = |value: &Value, name: &str| match value {
+        Value::Object(properties) => match properties.get(name) {
+            Some(Value::String(s)) => Some(s.to_string()),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    let message = field(&value, "message").unwrap_or_else(|| value.to_string());
+    let message_short = field(&value, "message_short").unwrap_or_else(|| message.clone());
+
+    EvalError {
+        message,
+        message_short,
+        span,
+        source: None,
+        trace: vec![],
+        thrown: Some(value),
+    }
+}
+
+// the value a catch block gets, source is used when the error didnt come from another file
+// a thrown object keeps all of its fields, any other thrown value is under value
+pub fn error_value(error: &EvalError, source: &Source) -> Value {
+    let source = error.source.as_deref().unwrap_or(source);
+
+    let (line, column) = source.location(error.span.start);
+
+    let location = HashMap::from([
+        ("file".to_string(), Value::String(source.name.as_str().into())),
+        ("line".to_string(), Value::Int(line as i64)),
+        ("column".to_string(), Value::Int(column as i64)),
+    ]);
+
+    let mut properties = match &error.thrown {
+        Some(Value::Object(properties)) => properties.as_ref().clone(),
+        Some(value) => HashMap::from([("value".to_string(), value.clone())]),
+        None => HashMap::new(),
+    };
+
+    properties.entry("message".to_string()).or_insert_with(|| Value::String(error.message.as_str().into()));
+    properties.entry("message_short".to_string()).or_insert_with(|| Value::String(error.message_short.as_str().into()));
+    properties.insert("location".to_string(), Value::Object(Rc::new(location)));
+
+    Value::Object(Rc::new(properties))
+}
+
+// null counts as false, same as in if conditions
+pub fn condition(value: Value, span: Span) -> Result<bool, EvalError> {
+    match value {
+        Value::Bool(b) => Ok(b),
+        Value::Null => Ok(false),
+
+        v => Err(EvalError {
+            message: format!("Condition must be a boolean, got {:?}", v),
+            message_short: "invalid condition".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+    }
+}
+
+// right_span is where the divisor is, division by zero points at it
+// two ints are by far the most common case, so they are handled first without
+// going through the error checks, anything else (or an int overflowing) goes to mixed
+#[inline(always)]
+pub fn binary(op: BinaryOp, left: Value, right: Value, span: Span, right_span: Span) -> Result<Value, EvalError> {
+    if let (Value::Int(l), Value::Int(r)) = (&left, &right) {
+        let (l, r) = (*l, *r);
+
+        let value = match op {
+            BinaryOp::Add => l.checked_add(r).map(Value::Int),
+            BinaryOp::Sub => l.checked_sub(r).map(Value::Int),
+            BinaryOp::Mul => l.checked_mul(r).map(Value::Int),
+            BinaryOp::Div => l.checked_div(r).map(Value::Int),
+            BinaryOp::Mod => l.checked_rem(r).map(Value::Int),
+            BinaryOp::Equal => Some(Value::Bool(l == r)),
+            BinaryOp::NotEqual => Some(Value::Bool(l != r)),
+            BinaryOp::LessThan => Some(Value::Bool(l < r)),
+            BinaryOp::LessThanOrEqual => Some(Value::Bool(l <= r)),
+            BinaryOp::GreaterThan => Some(Value::Bool(l > r)),
+            BinaryOp::GreaterThanOrEqual => Some(Value::Bool(l >= r)),
+        };
+
+        if let Some(value) = value {
+            return Ok(value);
+        }
+    }
+
+    mixed(op, left, right, span, right_span)
+}
+
+#[inline(never)]
+fn mixed(op: BinaryOp, left: Value, right: Value, span: Span, right_span: Span) -> Result<Value, EvalError> {
+    let symbol = match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Mod => "%",
+        BinaryOp::Equal => "==",
+        BinaryOp::NotEqual => "!=",
+        BinaryOp::LessThan => "<",
+        BinaryOp::LessThanOrEqual => "<=",
+        BinaryOp::GreaterThan => ">",
+        BinaryOp::GreaterThanOrEqual => ">=",
+    };
+
+    if matches!(op, BinaryOp::Div | BinaryOp::Mod) {
+        let divisor_is_zero = match right {
+            Value::Int(r) => r == 0,
+            Value::Float(r) => r == 0.0,
+            _ => false,
+        };
+
+        if divisor_is_zero {
+            return Err(EvalError {
+                message: if op == BinaryOp::Mod {
+                    "Modulo by zero".to_string()
+                } else {
+                    "Division by zero".to_string()
+                },
+                message_short: "divisor is zero".to_string(),
+                span: right_span,
+                source: None,
+                trace: vec![],
+                thrown: None,
+            });
+        }
+    }
+
+    let result = match (op, &left, &right) {
+        (BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod, Value::Int(l), Value::Int(r)) => {
+            let value = match op {
+                BinaryOp::Add => l.checked_add(*r),
+                BinaryOp::Sub => l.checked_sub(*r),
+                BinaryOp::Mul => l.checked_mul(*r),
+                BinaryOp::Div => l.checked_div(*r),
+                _ => l.checked_rem(*r),
+            };
+
+            match value {
+                Some(v) => Value::Int(v),
+                None => return Err(EvalError {
+                    message: format!("Integer overflow: {} {} {}", l, symbol, r),
+                    message_short: "integer overflow".to_string(),
+                    span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                }),
+            }
+        }
+
+        (BinaryOp::Add, Value::String(l), Value::String(r)) => Value::String(format!("{}{}", l, r).into()),
+
+        (BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod, _, _) => {
+            let (l, r) = match (&left, &right) {
+                (Value::Float(l), Value::Float(r)) => (*l, *r),
+                (Value::Int(l), Value::Float(r)) => (*l as f64, *r),
+                (Value::Float(l), Value::Int(r)) => (*l, *r as f64),
+
+                _ => {
+                    let (verb, short) = match op {
+                        BinaryOp::Add => ("add values", "cannot add"),
+                        BinaryOp::Sub => ("subtract values", "cannot subtract"),
+                        BinaryOp::Mul => ("multiply values", "cannot multiply"),
+                        BinaryOp::Div => ("divide values", "cannot divide"),
+                        _ => ("take modulo of values", "cannot take modulo"),
+                    };
+
+                    return Err(EvalError {
+                        message: format!("Cannot {}: {:?} {} {:?}", verb, left, symbol, right),
+                        message_short: short.to_string(),
+                        span,
+                        source: None,
+                        trace: vec![],
+                        thrown: None,
+                    });
+                }
+            };
+
+            Value::Float(match op {
+                BinaryOp::Add => l + r,
+                BinaryOp::Sub => l - r,
+                BinaryOp::Mul => l * r,
+                BinaryOp::Div => l / r,
+                _ => l % r,
+            })
+        }
+
+        (BinaryOp::Equal, _, _) => Value::Bool(values_equal(&left, &right)),
+        (BinaryOp::NotEqual, _, _) => Value::Bool(!values_equal(&left, &right)),
+
+        _ => {
+            let (l, r) = match (&left, &right) {
+                (Value::Int(l), Value::Int(r)) => {
+                    return Ok(Value::Bool(match op {
+                        BinaryOp::LessThan => l < r,
+                        BinaryOp::LessThanOrEqual => l <= r,
+                        BinaryOp::GreaterThan => l > r,
+                        _ => l >= r,
+                    }));
+                }
+
+                (Value::Float(l), Value::Float(r)) => (*l, *r),
+                (Value::Int(l), Value::Float(r)) => (*l as f64, *r),
+                (Value::Float(l), Value::Int(r)) => (*l, *r as f64),
+
+                _ => return Err(EvalError {
+                    message: format!("Cannot compare values: {:?} {} {:?}", left, symbol, right),
+                    message_short: "cannot compare".to_string(),
+                    span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                }),
+            };
+
+            Value::Bool(match op {
+                BinaryOp::LessThan => l < r,
+                BinaryOp::LessThanOrEqual => l <= r,
+                BinaryOp::GreaterThan => l > r,
+                _ => l >= r,
+            })
+        }
+    };
+
+    Ok(result)
+}
+
+fn values_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Int(l), Value::Int(r)) => l == r,
+        (Value::Float(l), Value::Float(r)) => l == r,
+        (Value::Int(l), Value::Float(r)) => (*l as f64) == *r,
+        (Value::Float(l), Value::Int(r)) => *l == (*r as f64),
+        (Value::Bool(l), Value::Bool(r)) => l == r,
+        (Value::String(l), Value::String(r)) => l == r,
+        (Value::Null, Value::Null) => true,
+        (Value::Struct(l), Value::Struct(r)) => Rc::ptr_eq(l, r),
+
+        (Value::Instance(l), Value::Instance(r)) => {
+            Rc::ptr_eq(&l.of, &r.of) && l.fields.iter().zip(r.fields.iter()).all(|(l, r)| values_equal(l, r))
+        }
+
+        (Value::Enum(l), Value::Enum(r)) => Rc::ptr_eq(l, r),
+        (Value::VariantConstructor(l, i), Value::VariantConstructor(r, j)) => Rc::ptr_eq(l, r) && i == j,
+
+        (Value::Variant(l), Value::Variant(r)) => {
+            Rc::ptr_eq(&l.of, &r.of) && l.index == r.index && l.values.iter().zip(r.values.iter()).all(|(l, r)| values_equal(l, r))
+        }
+
+        _ => false,
+    }
+}
+
+pub fn negate(value: Value, span: Span) -> Result<Value, EvalError> {
+    match value {
+        Value::Int(n) => n.checked_neg().map(Value::Int).ok_or_else(|| EvalError {
+            message: format!("Integer overflow: -({})", n),
+            message_short: "integer overflow".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+        Value::Float(f) => Ok(Value::Float(-f)),
+        _ => Err(EvalError {
+            message: format!("Cannot negate value: {:?}", value),
+            message_short: "cannot negate".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+    }
+}
+
+// f"..." writes each value like print does, strings are added as they are so
+// the result prints the same as printing the parts one after another
+pub fn interpolate(values: impl IntoIterator<Item = Value>) -> Value {
+    let mut text = String::new();
+
+    for value in values {
+        match value {
+            Value::String(s) => text.push_str(&s),
+            value => text.push_str(&value.to_string()),
+        }
+    }
+
+    Value::String(text.into())
+}
+
+pub fn get_property(object: Value, property: &str, span: Span) -> Result<Value, EvalError> {
+    match object {
+        Value::Module(symbols) => {
+            match symbols.get(property) {
+                Some(value) => Ok(value.clone()),
+                None => Err(EvalError {
+                    message: format!("Module has no property named {}", property),
+                    message_short: "no such property".to_string(),
+                    span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                }),
+            }
+        }
+
+        Value::Object(properties) => {
+            match properties.get(property) {
+                Some(value) => Ok(value.clone()),
+                None => {
+                    match crate::builtins::object::get_fn(property) {
+                        Some(value) => Ok(value),
+                        None => Err(EvalError {
+                            message: format!("Object has no property named {}", property),
+                            message_short: "no such property".to_string(),
+                            span,
+                            source: None,
+                            trace: vec![],
+                            thrown: None,
+                        }),
+                    }
+                }
+            }
+        }
+
+        Value::Instance(instance) => {
+            if let Some(index) = instance.of.field(property) {
+                return Ok(instance.fields[index].clone());
+            }
+
+            match instance.of.methods.get(property) {
+                Some(method) => Ok(Value::Function(method.clone())),
+                None => Err(EvalError {
+                    message: format!("{} has no field or method named {}", instance.of.name, property),
+                    message_short: "no such field".to_string(),
+                    span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                }),
+            }
+        }
+
+        // Point.origin() for methods that dont take self
+        Value::Struct(definition) => {
+            match definition.methods.get(property) {
+                Some(method) => Ok(Value::Function(method.clone())),
+                None => Err(EvalError {
+                    message: format!("{} has no method named {}", definition.name, property),
+                    message_short: "no such method".to_string(),
+                    span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                }),
+            }
+        }
+
+        // Shape.Empty is the variant itself, Shape.Circle has values so it has to be called
+        Value::Enum(definition) => {
+            match definition.variant(property) {
+                Some(index) if definition.variants[index].1.is_empty() => {
+                    Ok(Value::Variant(Rc::new(Variant { of: definition.clone(), index, values: vec![] })))
+                }
+
+                Some(index) => Ok(Value::VariantConstructor(definition.clone(), index)),
+
+                None => Err(EvalError {
+                    message: format!("{} has no variant named {}", definition.name, property),
+                    message_short: "no such variant".to_string(),
+                    span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                }),
+            }
+        }
+
+        // circle.r reads a value by the name the variant gives it
+        Value::Variant(variant) => {
+            match variant.of.variants[variant.index].1.iter().position(|name| name == property) {
+                Some(index) => Ok(variant.values[index].clone()),
+                None => Err(EvalError {
+                    message: format!("{}.{} has no value named {}", variant.of.name, variant.name(), property),
+                    message_short: "no such value".to_string(),
+                    span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                }),
+            }
+        }
+
+        Value::Array(_) => {
+            match crate::builtins::array::get_fn(property) {
+                Some(value) => Ok(value),
+                None => Err(EvalError {
+                    message: format!("Array has no property named {}", property),
+                    message_short: "no such property".to_string(),
+                    span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                }),
+            }
+        }
+
+        // the call looks the symbol up in the library by the property name
+        #[cfg(not(target_arch = "wasm32"))]
+        Value::FFILibrary(library) => Ok(Value::FFILibrary(library)),
+
+        _ => Err(EvalError {
+            message: format!("Cannot access property {} of {:?}", property, object),
+            message_short: "cannot access property".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+    }
+}
+
+fn array_index(elements: &[Value], i: i64, span: Span) -> Result<usize, EvalError> {
+    let idx = if i < 0 {
+        elements.len() as i64 + i
+    } else {
+        i
+    };
+
+    if idx < 0 || idx >= elements.len() as i64 {
+        return Err(EvalError {
+            message: format!("Array index out of bounds: {}", i),
+            message_short: "index out of bounds".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        });
+    }
+
+    Ok(idx as usize)
+}
+
+pub fn get_index(object: Value, index: Value, span: Span) -> Result<Value, EvalError> {
+    match (object, index) {
+        (Value::Array(elements), Value::Int(i)) => {
+            Ok(elements[array_index(&elements, i, span)?].clone())
+        }
+
+        (Value::Object(properties), Value::String(key)) => {
+            match properties.get(&*key) {
+                Some(value) => Ok(value.clone()),
+                None => Err(EvalError {
+                    message: format!("Object has no property named {}", key),
+                    message_short: "no such property".to_string(),
+                    span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                }),
+            }
+        }
+
+        (v, _) => Err(EvalError {
+            message: format!("Cannot index into value: {:?}", v),
+            message_short: "cannot index".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+    }
+}
+
+// returns the container with the property changed, the caller writes it back
+pub fn set_property(mut container: Value, property: &str, value: Value, span: Span) -> Result<Value, EvalError> {
+    match &mut container {
+        Value::Object(properties) => {
+            Rc::make_mut(properties).insert(property.to_string(), value);
+        }
+
+        // only the fields the struct declares exist
+        Value::Instance(instance) => match instance.of.field(property) {
+            Some(index) => Rc::make_mut(instance).fields[index] = value,
+
+            None => return Err(EvalError {
+                message: format!("{} has no field named {}", instance.of.name, property),
+                message_short: "no such field".to_string(),
+                span,
+                source: None,
+                trace: vec![],
+                thrown: None,
+            }),
+        },
+
+        v => return Err(EvalError {
+            message: format!("Cannot set property {} of {:?}", property, v),
+            message_short: "cannot set property".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+    }
+
+    Ok(container)
+}
+
+pub fn set_index(mut container: Value, index: Value, value: Value, object_span: Span, index_span: Span) -> Result<Value, EvalError> {
+    match (&mut container, index) {
+        (Value::Array(elements), Value::Int(i)) => {
+            let idx = array_index(elements, i, index_span)?;
+            Rc::make_mut(elements)[idx] = value;
+        }
+
+        (Value::Object(properties), Value::String(key)) => {
+            Rc::make_mut(properties).insert(key.to_string(), value);
+        }
+
+        (Value::Array(_), v) | (Value::Object(_), v) => return Err(EvalError {
+            message: format!("Invalid index type: {:?}", v),
+            message_short: "invalid index".to_string(),
+            span: index_span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+
+        (v, _) => return Err(EvalError {
+            message: format!("Cannot index into value: {:?}", v),
+            message_short: "cannot index".to_string(),
+            span: object_span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+    }
+
+    Ok(container)
+}
+
+pub fn object_key(key: Value, span: Span) -> Result<String, EvalError> {
+    match key {
+        Value::String(s) => Ok(s.to_string()),
+
+        v => Err(EvalError {
+            message: format!("Object keys must be strings, got {:?}", v),
+            message_short: "invalid key".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+    }
+}
+
+// the values an array pattern takes apart, the elements in order and then the rest as an array
+pub fn unpack_array(value: Value, count: usize, rest: bool, span: Span) -> Result<Vec<Value>, EvalError> {
+    let elements = match value {
+        Value::Array(elements) => elements,
+
+        v => return Err(EvalError {
+            message: format!("Cannot destructure {:?} as an array", v),
+            message_short: "not an array".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+    };
+
+    if elements.len() < count || (!rest && elements.len() > count) {
+        let expected = if rest { format!("at least {}", count) } else { count.to_string() };
+
+        return Err(EvalError {
+            message: format!("Expected an array with {} elements, but it has {}", expected, elements.len()),
+            message_short: format!("expected {} elements", expected),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        });
+    }
+
+    let mut values = elements[..count].to_vec();
+
+    if rest {
+        values.push(Value::Array(Rc::new(elements[count..].to_vec())));
+    }
+
+    Ok(values)
+}
+
+// the values of the keys an object pattern names, in the same order as the keys
+pub fn unpack_object<'a>(value: Value, keys: impl IntoIterator<Item = &'a Spanned<String>>, span: Span) -> Result<Vec<Value>, EvalError> {
+    let properties = match value {
+        Value::Object(properties) | Value::Module(properties) => properties,
+
+        v => return Err(EvalError {
+            message: format!("Cannot destructure {:?} as an object", v),
+            message_short: "not an object".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+    };
+
+    keys.into_iter()
+        .map(|key| properties.get(&key.node).cloned().ok_or_else(|| EvalError {
+            message: format!("Object has no property '{}'", key.node),
+            message_short: "missing property".to_string(),
+            span: key.span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }))
+        .collect()
+}
+
+fn bind(name: &str, slot: &Option<Slot>, value: Value, env: &Env) {
+    match slot {
+        Some(slot) => env.set(*slot, value),
+        None => env.set_global(name, value),
+    }
+}
+
+// checks a value against the pattern of a match arm, binding names along the way
+// shared with the vm, the names are slots in the same env
+pub fn match_pattern(pattern: &Spanned<Pattern>, value: &Value, env: &Env) -> Result<bool, EvalError> {
+    match &pattern.node {
+        Pattern::Wildcard => Ok(true),
+
+        Pattern::Binding { name, slot } => {
+            bind(name, slot, value.clone(), env);
+            Ok(true)
+        }
+
+        Pattern::Literal(literal) => Ok(values_equal(literal, value)),
+
+        Pattern::Range { start, end, inclusive } => {
+            let n = match value {
+                Value::Int(n) => *n as f64,
+                Value::Float(f) => *f,
+                _ => return Ok(false),
+            };
+
+            Ok(n >= *start as f64 && (n < *end as f64 || (*inclusive && n == *end as f64)))
+        }
+
+        Pattern::Or(alternatives) => {
+            for alternative in alternatives {
+                if match_pattern(alternative, value, env)? {
+                    return Ok(true);
+                }
+            }
+
+            Ok(false)
+        }
+
+        Pattern::Array { elements, rest } => {
+            let Value::Array(items) = value else {
+                return Ok(false);
+            };
+
+            if items.len() < elements.len() || (rest.is_none() && items.len() > elements.len()) {
+                return Ok(false);
+            }
+
+            for (element, item) in elements.iter().zip(items.iter()) {
+                if !match_pattern(element, item, env)? {
+                    return Ok(false);
+                }
+            }
+
+            match rest {
+                Some(rest) => match_pattern(rest, &Value::Array(Rc::new(items[elements.len()..].to_vec())), env),
+                None => Ok(true),
+            }
+        }
+
+        Pattern::Object { properties } => {
+            let (Value::Object(object) | Value::Module(object)) = value else {
+                return Ok(false);
+            };
+
+            for (key, property) in properties {
+                match object.get(&key.node) {
+                    Some(item) if match_pattern(property, item, env)? => {}
+                    _ => return Ok(false),
+                }
+            }
+
+            Ok(true)
+        }
+
+        Pattern::Variant { enum_name, variant: variant_name, values } => {
+            let Value::Variant(variant) = value else {
+                return Ok(false);
+            };
+
+            if variant.of.name != *enum_name {
+                return Ok(false);
+            }
+
+            // a typo in the pattern would otherwise never match anything
+            let Some(index) = variant.of.variant(&variant_name.node) else {
+                return Err(EvalError {
+                    message: format!("{} has no variant named {}", enum_name, variant_name.node),
+                    message_short: "no such variant".to_string(),
+                    span: variant_name.span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                });
+            };
+
+            if index != variant.index {
+                return Ok(false);
+            }
+
+            let Some(values) = values else {
+                return Ok(true);
+            };
+
+            if values.len() != variant.values.len() {
+                return Err(EvalError {
+                    message: format!("{}.{} has {} values, but the pattern has {}", enum_name, variant_name.node, variant.values.len(), values.len()),
+                    message_short: format!("expected {} values", variant.values.len()),
+                    span: pattern.span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                });
+            }
+
+            for (value, item) in values.iter().zip(variant.values.iter()) {
+                if !match_pattern(value, item, env)? {
+                    return Ok(false);
+                }
+            }
+
+            Ok(true)
+        }
+    }
+}
+
+pub fn no_match(value: &Value, span: Span) -> EvalError {
+    EvalError {
+        message: format!("No match arm matched {:?}", value),
+        message_short: "no arm matched this value".to_string(),
+        span,
+        source: None,
+        trace: vec![],
+        thrown: None,
+    }
+}
+
+// binds the names of a pattern to the parts of the value
+pub fn destructure(pattern: &Spanned<Pattern>, value: Value, env: &Env) -> Result<(), EvalError> {
+    match &pattern.node {
+        Pattern::Wildcard => {}
+
+        Pattern::Binding { name, slot } => bind(name, slot, value, env),
+
+        Pattern::Literal(_) | Pattern::Range { .. } | Pattern::Or(_) | Pattern::Variant { .. } => {
+            if !match_pattern(pattern, &value, env)? {
+                return Err(EvalError {
+                    message: format!("{:?} doesn't match the pattern", value),
+                    message_short: "pattern doesn't match".to_string(),
+                    span: pattern.span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                });
+            }
+        }
+
+        Pattern::Array { elements, rest } => {
+            let values = unpack_array(value, elements.len(), rest.is_some(), pattern.span)?;
+
+            for (element, value) in elements.iter().chain(rest.as_deref()).zip(values) {
+                destructure(element, value, env)?;
+            }
+        }
+
+        Pattern::Object { properties } => {
+            let values = unpack_object(value, properties.iter().map(|(key, _)| key), pattern.span)?;
+
+            for ((_, property), value) in properties.iter().zip(values) {
+                destructure(property, value, env)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn range_bound(bound: Value, which: &str, span: Span) -> Result<i64, EvalError> {
+    match bound {
+        Value::Int(n) => Ok(n),
+
+        v => Err(EvalError {
+            message: format!("Range {} must be an integer, got {:?}", which, v),
+            message_short: format!("invalid range {}", which),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+    }
+}
+
+// the sequence of values a for loop goes through, an enum rather than a boxed
+// iterator so a step through a range or array isnt a call through a vtable
+pub enum Iteration {
+    Range(std::ops::Range<i64>),
+    InclusiveRange(std::ops::RangeInclusive<i64>),
+    Array(Rc<Vec<Value>>, usize),
+    // the characters of a string or the keys of an object
+    Strings(std::vec::IntoIter<Value>),
+    Iterator(ValueIterator),
+}
+
+impl Iterator for Iteration {
+    type Item = Result<Value, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iteration::Range(range) => range.next().map(|i| Ok(Value::Int(i))),
+            Iteration::InclusiveRange(range) => range.next().map(|i| Ok(Value::Int(i))),
+
+            Iteration::Array(elements, i) => {
+                let element = elements.get(*i)?.clone();
+                *i += 1;
+
+                Some(Ok(element))
+            }
+
+            Iteration::Strings(values) => values.next().map(Ok),
+            Iteration::Iterator(iter) => iter.0.borrow_mut().next(),
+        }
+    }
+}
+
+// turns a value into the sequence of values a for loop goes through
+pub fn iterate(value: Value, span: Span) -> Result<Iteration, EvalError> {
+    match value {
+        Value::Range(start, end) => Ok(Iteration::Range(start..end)),
+        Value::InclusiveRange(start, end) => Ok(Iteration::InclusiveRange(start..=end)),
+        Value::Array(elements) => Ok(Iteration::Array(elements, 0)),
+
+        Value::String(s) => {
+            let chars = s.chars().map(|c| Value::String(c.to_string().into())).collect::<Vec<_>>();
+            Ok(Iteration::Strings(chars.into_iter()))
+        }
+
+        // keys are sorted so the order doesnt change between runs
+        Value::Object(properties) => {
+            let mut keys = properties.keys().cloned().collect::<Vec<String>>();
+            keys.sort();
+
+            Ok(Iteration::Strings(keys.into_iter().map(|k| Value::String(k.into())).collect::<Vec<_>>().into_iter()))
+        }
+
+        Value::Iterator(iter) => Ok(Iteration::Iterator(iter)),
+
+        v => Err(EvalError {
+            message: format!("Cannot iterate over value: {:?}", v),
+            message_short: "cannot iterate".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+    }
+}
+
+pub fn check_arity(name: &str, arity: Arity, args: &[Spanned<Value>], span: Span) -> Result<(), EvalError> {
+    if arity.accepts(args.len()) {
+        return Ok(());
+    }
+
+    let expected = match arity.max {
+        Some(max) if max == arity.min => max.to_string(),
+        Some(max) => format!("{} to {}", arity.min, max),
+        None => format!("at least {}", arity.min),
+    };
+
+    match arity.max {
+        Some(max) if args.len() > max => Err(EvalError {
+            message: format!("Function {} expects {} arguments, got {}", name, expected, args.len()),
+            message_short: format!("{} arguments too many", args.len() - max),
+            span: SimpleSpan::from(args[max].span.start..args[args.len() - 1].span.end),
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+
+        _ => Err(EvalError {
+            message: format!("Function {} expects {} arguments, got {}", name, expected, args.len()),
+            message_short: format!("{} arguments too few", arity.min - args.len()),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+    }
+}
+
+// the frame a call runs in, the args (self first for a method) take the first slots
+// and the ones past the named args are collected into an array for ...rest
+pub fn call_env(function: &Function, mut args: Vec<Value>, skipped: Vec<usize>) -> Env {
+    let env = function.captured.call(function.frame_size, args.len(), skipped);
+
+    let rest = match function.arity.max {
+        None => Some(args.split_off(args.len().min(function.args.len() - 1))),
+        Some(_) => None,
+    };
+
+    for (index, arg) in args.into_iter().enumerate() {
+        env.set(Slot { depth: 0, index }, arg);
+    }
+
+    if let Some(rest) = rest {
+        env.set(Slot { depth: 0, index: function.args.len() - 1 }, Value::Array(Rc::new(rest)));
+    }
+
+    env
+}
+
+fn missing_arg(function: &str, arg: &str, span: Span) -> EvalError {
+    EvalError {
+        message: format!("Function {} is missing argument {}", function, arg),
+        message_short: format!("{} is missing", arg),
+        span,
+        source: None,
+        trace: vec![],
+        thrown: None,
+    }
+}
+
+// f(1, timeout: 5), every named arg goes where names has it, after the positional ones
+// the args in between that nothing was passed for are None
+fn place_named(function: &str, names: &[String], args: Vec<Spanned<Value>>, named: NamedArgs) -> Result<Vec<Option<Spanned<Value>>>, EvalError> {
+    let mut placed = args.into_iter().map(Some).collect::<Vec<_>>();
+
+    for (name, value) in named {
+        let Some(index) = names.iter().position(|arg| *arg == name.node) else {
+            return Err(EvalError {
+                message: format!("Function {} has no argument named {}", function, name.node),
+                message_short: "unknown argument".to_string(),
+                span: name.span,
+                source: None,
+                trace: vec![],
+                thrown: None,
+            });
+        };
+
+        if placed.len() <= index {
+            placed.resize(index + 1, None);
+        }
+
+        if placed[index].is_some() {
+            return Err(EvalError {
+                message: format!("Argument {} of function {} is passed twice", name.node, function),
+                message_short: "passed twice".to_string(),
+                span: name.span,
+                source: None,
+                trace: vec![],
+                thrown: None,
+            });
+        }
+
+        placed[index] = Some(value);
+    }
+
+    Ok(placed)
+}
+
+// natives, structs and variants have no defaults, so every arg a named one jumps over is missing
+fn named_args(function: &str, names: &[String], args: Vec<Spanned<Value>>, named: NamedArgs, span: Span) -> Result<Vec<Spanned<Value>>, EvalError> {
+    if named.is_empty() {
+        return Ok(args);
+    }
+
+    place_named(function, names, args, named)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, arg)| arg.ok_or_else(|| missing_arg(function, &names[index], span)))
+        .collect()
+}
+
+// the args for a call to a modu function, skip is 1 when self is passed before them
+// skipped are the args named args jumped over, they get their defaults like the ones left out at the end
+pub fn function_args(function: &Function, skip: usize, args: Vec<Spanned<Value>>, named: NamedArgs, span: Span) -> Result<(Vec<Spanned<Value>>, Vec<usize>), EvalError> {
+    let arity = match skip {
+        0 => function.arity,
+        _ => function.arity.without_self(),
+    };
+
+    if named.is_empty() {
+        check_arity(&function.name, arity, &args, span)?;
+        return Ok((args, vec![]));
+    }
+
+    // ...rest cant be named
+    let names = &function.args[skip..function.args.len() - function.arity.max.is_none() as usize];
+    let mut placed = place_named(&function.name, names, args, named)?;
+
+    if placed.len() < arity.min {
+        placed.resize(arity.min, None);
+    }
+
+    let mut args = vec![];
+    let mut skipped = vec![];
+
+    for (index, arg) in placed.into_iter().enumerate() {
+        match arg {
+            Some(arg) => args.push(arg),
+            None if index < arity.min => return Err(missing_arg(&function.name, &names[index], span)),
+
+            None => {
+                skipped.push(index + skip);
+                args.push(Spanned { node: Value::Null, span });
+            }
+        }
+    }
+
+    check_arity(&function.name, arity, &args, span)?;
+
+    Ok((args, skipped))
+}
+
+// Point(1, 2) without an init method, the arguments are the fields in order
+// with one, the fields start as null and init sets them (it is called by eval::call or the vm)
+pub fn new_instance(definition: &Rc<Struct>, args: Vec<Spanned<Value>>, span: Span) -> Result<Value, EvalError> {
+    let fields = match definition.init() {
+        Some(_) => vec![Value::Null; definition.fields.len()],
+
+        None => {
+            check_arity(&definition.name, Arity::exact(definition.fields.len()), &args, span)?;
+            args.into_iter().map(|arg| arg.node).collect()
+        }
+    };
+
+    Ok(Value::Instance(Rc::new(Instance { of: definition.clone(), fields })))
+}
+
+pub fn make_struct(name: &str, fields: &[String], methods: impl IntoIterator<Item = (String, Function)>) -> Value {
+    Value::Struct(Rc::new(Struct {
+        name: name.to_string(),
+        fields: fields.to_vec(),
+        methods: methods.into_iter().map(|(method_name, method)| (method_name, Rc::new(method))).collect(),
+    }))
+}
+
+pub fn make_enum(name: &str, variants: &[(String, Vec<String>)]) -> Value {
+    Value::Enum(Rc::new(Enum { name: name.to_string(), variants: variants.to_vec() }))
+}
+
+// calls anything that isnt a modu function, receiver is the object and property
+// name for calls like arr.push(1), which is passed as self if the function wants it
+// named args are matched to the names in args, or the fields for structs and variants
+pub fn call_native(function: Value, mut args: Vec<Spanned<Value>>, named: NamedArgs, receiver: Option<(Spanned<Value>, &str)>, span: Span) -> Result<InternalFunctionResponse, EvalError> {
+    match function {
+        Value::InternalFunction(function) => {
+            if function.args.iter().any(|arg| arg == "self") && let Some((object, _)) = receiver {
+                args.insert(0, object);
+            }
+
+            if function.args.iter().any(|arg| arg == "__args__") {
+                args = named_args(&function.name, &[], args, named, span)?;
+            } else {
+                args = named_args(&function.name, &function.args, args, named, span)?;
+                check_arity(&function.name, Arity::exact(function.args.len()), &args, span)?;
+            }
+
+            (function.func)(args).map_err(|(msg, span)| EvalError {
+                message: msg.clone(),
+                message_short: msg,
+                span,
+                source: None,
+                trace: vec![],
+                thrown: None,
+            })
+        }
+
+        Value::Struct(definition) => Ok(InternalFunctionResponse {
+            return_value: new_instance(&definition, named_args(&definition.name, &definition.fields, args, named, span)?, span)?,
+            replace_self: None,
+        }),
+
+        Value::VariantConstructor(definition, index) => {
+            let (name, fields) = &definition.variants[index];
+            let name = format!("{}.{}", definition.name, name);
+            let args = named_args(&name, fields, args, named, span)?;
+            check_arity(&name, Arity::exact(fields.len()), &args, span)?;
+
+            Ok(InternalFunctionResponse {
+                return_value: Value::Variant(Rc::new(Variant { of: definition.clone(), index, values: args.into_iter().map(|arg| arg.node).collect() })),
+                replace_self: None,
+            })
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        Value::FFILibrary(library) => {
+            let name = match receiver {
+                Some((_, name)) => name,
+                None => unreachable!(),
+            };
+
+            let args = named_args(name, &[], args, named, span)?;
+
+            match crate::libraries::ffi::execute_ffi_call(&library, name, args) {
+                Ok(value) => Ok(InternalFunctionResponse {
+                    return_value: value,
+                    replace_self: None,
+                }),
+                Err(msg) => Err(EvalError {
+                    message: msg.clone(),
+                    message_short: msg,
+                    span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                }),
+            }
+        }
+
+        v => Err(EvalError {
+            message: format!("{:?} is not a function", v),
+            message_short: "not a function".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }),
+    }
+}
+
+fn eval_condition(expr: &SpannedExpr, env: &Env) -> Result<bool, Box<EvalError>> {
+    Ok(condition(eval(expr, env)?.unwrap(), expr.span)?)
+}
+
+// writes the value into whatever the target points at, for paths like a.b[0]
+// the containers are read, modified and then written back one level up
+fn assign(target: &SpannedExpr, value: Value, env: &Env) -> Result<(), EvalError> {
+    match &target.node {
+        Expr::Identifier(name) => {
+            if !env.has_global(name) {
+                return Err(EvalError {
+                    message: format!("Cannot assign to undefined variable: {}", name),
+                    message_short: "not defined".to_string(),
+                    span: target.span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                });
+            }
+
+            env.set_global(name, value);
+
+            Ok(())
+        }
+
+        Expr::Local { slot } => {
+            env.set(*slot, value);
+
+            Ok(())
+        }
+
+        Expr::PropertyAccess { object, property } => {
+            let container = eval(object, env)?.unwrap();
+            let container = set_property(container, property, value, object.span)?;
+
+            assign(object, container, env)
+        }
+
+        Expr::IndexAccess { object, index } => {
+            let container = eval(object, env)?.unwrap();
+            let index_value = eval(index, env)?.unwrap();
+            let container = set_index(container, index_value, value, object.span, index.span)?;
+
+            assign(object, container, env)
+        }
+
+        _ => Err(invalid_target(target.span)),
+    }
+}
+
+pub fn invalid_target(span: Span) -> EvalError {
+    EvalError {
+        message: "Invalid assignment target, only variables, properties and indexes can be assigned to".to_string(),
+        message_short: "cannot assign to this".to_string(),
+        span,
+        source: None,
+        trace: vec![],
+        thrown: None,
+    }
+}
+
+// the values of the indexes in a target like a[i].b[j], outermost first, so x[f()] += 1
+// can read and assign x[f()] with f called once
+fn target_indexes(target: &SpannedExpr, env: &Env) -> Result<Vec<Value>, EvalError> {
+    match &target.node {
+        Expr::Identifier(_) | Expr::Local { .. } => Ok(vec![]),
+        Expr::PropertyAccess { object, .. } => target_indexes(object, env),
+
+        Expr::IndexAccess { object, index } => {
+            let mut indexes = target_indexes(object, env)?;
+            indexes.push(eval(index, env)?.unwrap());
+
+            Ok(indexes)
+        }
+
+        _ => Err(invalid_target(target.span)),
+    }
+}
+
+// what the target is now, with the values target_indexes found for it
+// the vm runs compound assignments through here and write_target as well
+pub fn read_target(target: &SpannedExpr, indexes: &[Value], env: &Env) -> Result<Value, EvalError> {
+    match &target.node {
+        Expr::PropertyAccess { object, property } => get_property(read_target(object, indexes, env)?, property, target.span),
+
+        Expr::IndexAccess { object, .. } => {
+            let (index, indexes) = indexes.split_last().unwrap();
+            get_index(read_target(object, indexes, env)?, index.clone(), target.span)
+        }
+
+        _ => Ok(eval(target, env)?.unwrap()),
+    }
+}
+
+// same as assign, but with the indexes already evaluated
+pub fn write_target(target: &SpannedExpr, indexes: &[Value], value: Value, env: &Env) -> Result<(), EvalError> {
+    match &target.node {
+        Expr::PropertyAccess { object, property } => {
+            let container = read_target(object, indexes, env)?;
+            let container = set_property(container, property, value, object.span)?;
+
+            write_target(object, indexes, container, env)
+        }
+
+        Expr::IndexAccess { object, index } => {
+            let (index_value, indexes) = indexes.split_last().unwrap();
+            let container = read_target(object, indexes, env)?;
+            let container = set_index(container, index_value.clone(), value, object.span, index.span)?;
+
+            write_target(object, indexes, container, env)
+        }
+
+        _ => assign(target, value, env),
+    }
+}
+
+// unlabeled break/continue always target the innermost loop
+fn targets_loop(target: &Option<String>, label: &Option<String>) -> bool {
+    match target {
+        None => true,
+        Some(_) => target == label,
+    }
+}
+
+pub fn is_assignable(expr: &SpannedExpr) -> bool {
+    match &expr.node {
+        Expr::Identifier(_) | Expr::Local { .. } => true,
+        Expr::PropertyAccess { object, .. } | Expr::IndexAccess { object, .. } => is_assignable(object),
+        _ => false,
+    }
+}
+
+fn eval_args(args: &[SpannedExpr], env: &Env) -> Result<Vec<Spanned<Value>>, EvalError> {
+    args.iter()
+        .map(|arg| Ok(Spanned {
+            node: eval(arg, env)?.unwrap(),
+            span: arg.span,
+        }))
+        .collect()
+}
+
+fn eval_named(named: &[(Spanned<String>, SpannedExpr)], env: &Env) -> Result<NamedArgs, EvalError> {
+    named.iter()
+        .map(|(name, arg)| Ok((name.clone(), Spanned {
+            node: eval(arg, env)?.unwrap(),
+            span: arg.span,
+        })))
+        .collect()
+}
+
+// kept out of eval so its stack frame stays small, eval recurses once per call
+#[inline(never)]
+// with tail set a modu function isnt called yet, it is returned as Flow::TailCall instead
+fn call(expr: &SpannedExpr, callee: &SpannedExpr, args: &[SpannedExpr], named: &[(Spanned<String>, SpannedExpr)], env: &Env, tail: bool) -> Result<Flow, Box<EvalError>> {
+    // for obj.method() the object is evaluated once and passed along as the receiver
+    let (function, receiver) = match &callee.node {
+        Expr::PropertyAccess { object, property } => {
+            let object_value = value!(eval(object, env)?);
+            let function = get_property(object_value.clone(), property, callee.span)?;
+
+            (function, Some((object, property, object_value)))
+        }
+
+        _ => (value!(eval(callee, env)?), None),
+    };
+
+    let evaluated_args = eval_args(args, env)?;
+    let named = eval_named(named, env)?;
+
+    let function = match function {
+        // obj.method() passes obj as self and writes self back afterwards, so changes to it stick
+        Value::Function(function) if function.takes_self() && let Some((object, _, this @ Value::Instance(_))) = receiver => {
+            let (mut evaluated_args, skipped) = function_args(&function, 1, evaluated_args, named, expr.span)?;
+
+            let mut this = this;
+            evaluated_args.insert(0, Spanned { node: this.clone(), span: object.span });
+
+            let value = call_function(function, evaluated_args, skipped, expr.span, env.source.clone(), Some(&mut this))?.unwrap();
+
+            if is_assignable(object) {
+                assign(object, this, env)?;
+            }
+
+            return Ok(Flow::Continue(value));
         }
+
+        Value::Function(function) => function,
+
+        // Point(1, 2) with an init method, the instance is what self is once init is done
+        Value::Struct(ref definition) if let Some(init) = definition.init() => {
+            let (mut evaluated_args, skipped) = function_args(init, 1, evaluated_args, named, expr.span)?;
+
+            let mut this = new_instance(definition, vec![], expr.span)?;
+            evaluated_args.insert(0, Spanned { node: this.clone(), span: expr.span });
+
+            call_function(init.clone(), evaluated_args, skipped, expr.span, env.source.clone(), Some(&mut this))?;
+
+            return Ok(Flow::Continue(this));
+        }
+
+        native => {
+            let response = call_native(
+                native,
+                evaluated_args,
+                named,
+                receiver.as_ref().map(|(object, property, value)| (Spanned { node: value.clone(), span: object.span }, property.as_str())),
+                expr.span,
+            )?;
+
+            if let Some(replace_self) = response.replace_self
+                && let Some((object, _, _)) = receiver
+                && is_assignable(object)
+            {
+                assign(object, replace_self, env)?;
+            }
+
+            return Ok(Flow::Continue(response.return_value));
+        }
+    };
+
+    let (evaluated_args, skipped) = function_args(&function, 0, evaluated_args, named, expr.span)?;
+
+    if tail {
+        return Ok(Flow::TailCall(Box::new(TailCall { function, args: evaluated_args, skipped, span: expr.span })));
+    }
+
+    call_function(function, evaluated_args, skipped, expr.span, env.source.clone(), None)
+}
+
+// caller is the file the call is in, this is set for methods and gets what self is at the end
+fn call_function(function: Rc<Function>, args: Vec<Spanned<Value>>, skipped: Vec<usize>, span: Span, caller: Rc<Source>, this: Option<&mut Value>) -> Result<Flow, Box<EvalError>> {
+    let depth = DEPTH.get();
+
+    if depth >= max_depth() {
+        return Err(too_deep(span).into());
+    }
+
+    DEPTH.set(depth + 1);
+    let result = run_function(function, args, skipped, span, caller, this);
+    DEPTH.set(depth);
+
+    result
+}
+
+// runs the body of a function, and then the function it tail calls and so on,
+// so return f(x) in a loop doesnt make eval recurse any deeper
+fn run_function(mut function: Rc<Function>, mut args: Vec<Spanned<Value>>, mut skipped: Vec<usize>, mut span: Span, mut caller: Rc<Source>, mut this: Option<&mut Value>) -> Result<Flow, Box<EvalError>> {
+    // the call this started as, traces keep it when tail calls have replaced it
+    let mut replaced: Option<TraceFrame> = None;
+
+    loop {
+        // the body runs in a new frame on top of where the function was defined
+        let call_env = call_env(&function, args.into_iter().map(|arg| arg.node).collect(), skipped);
+
+        // eval recurses a few times for every call, so the stack is grown
+        // on the heap when it runs low instead of overflowing
+        let flow = stacker::maybe_grow(STACK_RED_ZONE, STACK_GROW_SIZE, || eval(&function.body, &call_env)).map_err(|mut e| {
+            e.source.get_or_insert_with(|| function.captured.source.clone());
+            e.trace.push(TraceFrame {
+                function: function.name.clone(),
+                span,
+                source: caller.clone(),
+            });
+            e.trace.extend(replaced.clone());
+            e
+        })?;
+
+        // self is read before a tail call could leave the method
+        let is_method = match this.take() {
+            Some(this) => {
+                *this = call_env.get(Slot { depth: 0, index: 0 });
+                true
+            }
+
+            None => false,
+        };
+
+        let unexpected = |message: &str, message_short: &str| EvalError {
+            message: message.to_string(),
+            message_short: message_short.to_string(),
+            span,
+            source: Some(caller.clone()),
+            trace: vec![],
+            thrown: None,
+        };
+
+        return match flow {
+            Flow::Continue(v) => Ok(Flow::Continue(v)),
+            Flow::Return(v) => Ok(Flow::Continue(v)),
+            Flow::Break(_) => Err(unexpected("Unexpected break in function", "unexpected break").into()),
+            Flow::Skip(_) => Err(unexpected("Unexpected skip in function", "unexpected skip").into()),
+
+            // the vm keeps the frame of a method around until it returns, so this does too
+            Flow::TailCall(next) if is_method => {
+                call_function(next.function, next.args, next.skipped, next.span, function.captured.source.clone(), None)
+            }
+
+            Flow::TailCall(next) => {
+                replaced.get_or_insert_with(|| TraceFrame { function: function.name.clone(), span, source: caller.clone() });
+
+                caller = function.captured.source.clone();
+                function = next.function;
+                args = next.args;
+                skipped = next.skipped;
+                span = next.span;
+
+                continue;
+            }
+        };
+    }
+}
+
+// makes the call a Flow::TailCall is waiting for, for the places it cant wait any longer
+// like try blocks and return outside of a function
+pub fn finish_tail_call(flow: Flow, env: &Env) -> Result<Flow, Box<EvalError>> {
+    match flow {
+        Flow::TailCall(call) => {
+            let value = call_function(call.function, call.args, call.skipped, call.span, env.source.clone(), None)?.unwrap();
+            Ok(Flow::Return(value))
+        }
+
+        flow => Ok(flow),
     }
 }
 
-impl std::fmt::Display for EvalError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.message)
-    }
-}
+// the vm runs imports through here as well
+#[inline(never)]
+pub fn import(name: &String, import_as: &Option<String>, span: Span, env: &Env) -> Result<Value, EvalError> {
+    let import_as = match import_as {
+        Some(as_name) => as_name.clone(),
+        None => name.clone(),
+    };
+
+    let mut path: std::path::PathBuf = std::path::PathBuf::new();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        path = std::env::current_dir().unwrap();
+        
+        let sys_args = std::env::args().collect::<Vec<String>>();
+        if sys_args.len() > 2 && sys_args[1] == "run" {
+            path.push(&sys_args[2]);
+            path.pop();
+        }
+
+        if env.has_global("CURRENTLY_PARSING_MODULE_PATH") {
+            if let Value::String(current_module_path) = env.get_global("CURRENTLY_PARSING_MODULE_PATH").unwrap() {
+                let mut module_path = std::path::PathBuf::from(&*current_module_path);
+                module_path.pop();
+                path = module_path;
+            }
+        }
+
+        if env.has_global("CURRENTLY_PARSING_PACKAGE_NAME") {
+            if let Value::String(current_package_name) = env.get_global("CURRENTLY_PARSING_PACKAGE_NAME").unwrap() {
+                path.push(".modu");
+                path.push("packages");
+                path.push(&*current_package_name);
+            }
+        }
+    }
+
+    if name.ends_with(".modu") {
+        path.push(name);
+        
+        let source = std::fs::read_to_string(path.clone()).map_err(|e| EvalError {
+            message: format!("Failed to read module file {}: {}", name, e),
+            message_short: "failed to read module".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        })?;
+
+        let new_context = crate::utils::create_context();
+        new_context.borrow_mut().insert(
+            "CURRENTLY_PARSING_MODULE_PATH".to_string(),
+            Value::String(path.to_str().unwrap().into())
+        );
+
+        crate::parser::parse(&source, path.to_str().unwrap(), &new_context);
+
+        if import_as == "*" {
+            // the module's functions still read their globals from new_context, so copy them over instead of emptying it
+            for (k, v) in new_context.borrow().iter() {
+                env.set_global(k, v.clone());
+            }
+        } else {
+            let mut symbols = HashMap::new();
+
+            for (k, v) in new_context.borrow().iter().filter(|(k, _)| !crate::utils::create_context().borrow().contains_key(*k)) {
+                symbols.insert(k.clone(), v.clone());
+            }
+
+            env.set_global(&import_as.replace(".modu", ""), Value::Module(Rc::new(symbols)));
+        }
+    } else {
+        match crate::libraries::get_package(name) {
+            Some(module) => {
+                if import_as == "*" {
+                    if let Value::Module(symbols) = module {
+                        for (k, v) in symbols.iter() {
+                            env.set_global(k, v.clone());
+                        }
+                    } else {
+                        return Err(EvalError {
+                            message: format!("Package {} is not a module", name),
+                            message_short: "not a module".to_string(),
+                            span,
+                            source: None,
+                            trace: vec![],
+                            thrown: None,
+                        });
+                    }
+                } else {
+                    env.set_global(&import_as, module);
+                }
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            None => {
+                path.push(".modu");
+                path.push("packages");
+                path.push(name);
+                path.push("lib.modu");
+
+                if !path.exists() {
+                    return Err(EvalError {
+                        message: format!("Package {} does not exist or is not installed", name),
+                        message_short: "package not found".to_string(),
+                        span,
+                        source: None,
+                        trace: vec![],
+                        thrown: None,
+                    });
+                }
+
+                let source = std::fs::read_to_string(path.clone()).map_err(|e| EvalError {
+                    message: format!("Failed to read module file for package {}: {}", name, e),
+                    message_short: "failed to read module".to_string(),
+                    span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                })?;
 
-pub fn eval<'src>(expr: &'src SpannedExpr, context: &mut HashMap<String, Expr>) -> Result<Flow, EvalError> {    
-    match &expr.node {
-        Expr::Int(n) => Ok(Flow::Continue(Expr::Int(*n))),
-        Expr::Float(f) => Ok(Flow::Continue(Expr::Float(*f))),
-        Expr::String(s) => Ok(Flow::Continue(Expr::String(s.clone()))),
-        Expr::Bool(b) => Ok(Flow::Continue(Expr::Bool(*b))),
-        Expr::Null => Ok(Flow::Continue(Expr::Null)),
+                let new_context = crate::utils::create_context();
+                new_context.borrow_mut().insert(
+                    "CURRENTLY_PARSING_PACKAGE_PATH".to_string(),
+                    Value::String(path.to_str().unwrap().into())
+                );
+                new_context.borrow_mut().insert(
+                    "CURRENTLY_PARSING_PACKAGE_NAME".to_string(),
+                    Value::String(name.as_str().into()),
+                );
 
-        Expr::PropertyAccess { object, property } => {
-            let object = eval(object, context)?.unwrap();
+                crate::parser::parse(&source, path.to_str().unwrap(), &new_context);
 
-            match object {
-                Expr::Module { symbols } => {
-                    match symbols.get(property) {
-                        Some(value) => Ok(Flow::Continue(value.node.clone())),
-                        None => Err(EvalError {
-                            message: format!("Module has no property named {}", property),
-                            message_short: "no such property".to_string(),
-                            span: expr.span,
-                        }),
-                    }
-                }
-                
-                Expr::Object { properties } => {
-                    match properties.get(property) {
-                        Some(value) => Ok(Flow::Continue(value.clone())),
-                        None => {
-                            match crate::builtins::object::get_fn(property) {
-                                Some(value) => Ok(Flow::Continue(value)),
-                                None => Err(EvalError {
-                                    message: format!("Object has no property named {}", property),
-                                    message_short: "no such property".to_string(),
-                                    span: expr.span,
-                                }),
-                            }
-                        }
+                if import_as == "*" {
+                    for (k, v) in new_context.borrow().iter() {
+                        env.set_global(k, v.clone());
                     }
-                }
+                } else {
+                    let mut symbols = HashMap::new();
 
-                Expr::Array(_) => {
-                    match crate::builtins::array::get_fn(property) {
-                        Some(value) => Ok(Flow::Continue(value)),
-                        None => Err(EvalError {
-                            message: format!("Array has no property named {}", property),
-                            message_short: "no such property".to_string(),
-                            span: expr.span,
-                        }),
+                    for (k, v) in new_context.borrow().iter().filter(|(k, _)| !crate::utils::create_context().borrow().contains_key(*k)) {
+                        symbols.insert(k.clone(), v.clone());
                     }
-                }
 
-                #[cfg(not(target_arch = "wasm32"))]
-                Expr::FFILibrary(library) => {
-                    Ok(Flow::Continue(Expr::FFILibrary(library)))
+                    env.set_global(&import_as.replace(".modu", ""), Value::Module(Rc::new(symbols)));
                 }
+            }
 
-                _ => Err(EvalError {
-                    message: format!("Cannot access property {} of {:?}", property, object),
-                    message_short: "cannot access property".to_string(),
-                    span: expr.span,
-                }),
+            #[cfg(target_arch = "wasm32")]
+            None => {
+                return Err(EvalError {
+                    message: format!("Could not find package {}", name),
+                    message_short: "package not found".to_string(),
+                    span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                });
             }
         }
 
-        Expr::Neg(inner) => {
-            let value = eval(inner, context)?.unwrap();
+       
+    }
 
-            match value {
-                Expr::Int(n) => Ok(Flow::Continue(Expr::Int(-n))),
-                Expr::Float(f) => Ok(Flow::Continue(Expr::Float(-f))),
-                _ => Err(EvalError {
-                    message: format!("Cannot negate value: {:?}", value),
-                    message_short: "cannot negate".to_string(),
-                    span: expr.span,
-                }),
-            }
+    Ok(Value::Null)
+}
+
+// literals, locals and operators are the most common nodes by far, handling them
+// before the big match below means they dont pay for its stack frame
+pub fn eval(expr: &SpannedExpr, env: &Env) -> Result<Flow, Box<EvalError>> {
+    match &expr.node {
+        Expr::Int(n) => Ok(Flow::Continue(Value::Int(*n))),
+        Expr::Local { slot } => Ok(Flow::Continue(env.get(*slot))),
+        Expr::Add(left, right) => eval_binary(BinaryOp::Add, expr, left, right, env),
+        Expr::Sub(left, right) => eval_binary(BinaryOp::Sub, expr, left, right, env),
+        Expr::Mul(left, right) => eval_binary(BinaryOp::Mul, expr, left, right, env),
+        Expr::Div(left, right) => eval_binary(BinaryOp::Div, expr, left, right, env),
+        Expr::Mod(left, right) => eval_binary(BinaryOp::Mod, expr, left, right, env),
+        Expr::Equal(left, right) => eval_binary(BinaryOp::Equal, expr, left, right, env),
+        Expr::NotEqual(left, right) => eval_binary(BinaryOp::NotEqual, expr, left, right, env),
+        Expr::LessThan(left, right) => eval_binary(BinaryOp::LessThan, expr, left, right, env),
+        Expr::LessThanOrEqual(left, right) => eval_binary(BinaryOp::LessThanOrEqual, expr, left, right, env),
+        Expr::GreaterThan(left, right) => eval_binary(BinaryOp::GreaterThan, expr, left, right, env),
+        Expr::GreaterThanOrEqual(left, right) => eval_binary(BinaryOp::GreaterThanOrEqual, expr, left, right, env),
+
+        Expr::CompoundAssign { op, target, value } if matches!(target.node, Expr::Local { .. } | Expr::Identifier(_)) => {
+            eval_compound_assign(*op, target, value, env)
         }
 
-        Expr::Add(left, right) => {
-            let left_value = eval(left, context)?.unwrap();
-            let right_value = eval(right, context)?.unwrap();
+        _ => eval_node(expr, env),
+    }
+}
 
-            match (left_value, right_value) {
-                (Expr::Int(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Int(l + r))),
-                (Expr::Float(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Float(l + r))),
-                (Expr::Int(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Float(l as f64 + r))),
-                (Expr::Float(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Float(l + r as f64))),
-                (Expr::String(l), Expr::String(r)) => Ok(Flow::Continue(Expr::String(l + &r))),
+// a plain variable, like total += i, skips finding and rewriting a path
+fn eval_compound_assign(op: BinaryOp, target: &SpannedExpr, right: &SpannedExpr, env: &Env) -> Result<Flow, Box<EvalError>> {
+    let current = operand!(target, env);
+    let right_value = operand!(right, env);
+    let value = binary(op, current, right_value, Span::from(target.span.start..right.span.end), right.span)?;
+
+    // reading it above already failed if the variable isnt defined
+    match &target.node {
+        Expr::Local { slot } => env.set(*slot, value),
+        Expr::Identifier(name) => env.set_global(name, value),
+        _ => unreachable!("eval only sends plain variables here"),
+    }
 
-                _ => Err(EvalError {
-                    message: format!("Cannot add values: {:?} + {:?}", left.node, right.node),
-                    message_short: "cannot add".to_string(),
-                    span: expr.span,
-                }),
-            }
+    Ok(Flow::Continue(Value::Null))
+}
+
+fn eval_binary(op: BinaryOp, expr: &SpannedExpr, left: &SpannedExpr, right: &SpannedExpr, env: &Env) -> Result<Flow, Box<EvalError>> {
+    let left_value = operand!(left, env);
+    let right_value = operand!(right, env);
+
+    Ok(Flow::Continue(binary(op, left_value, right_value, expr.span, right.span)?))
+}
+
+#[inline(never)]
+fn eval_node<'src>(expr: &'src SpannedExpr, env: &Env) -> Result<Flow, Box<EvalError>> {
+    match &expr.node {
+        Expr::Int(_) | Expr::Local { .. }
+        | Expr::Add(..) | Expr::Sub(..) | Expr::Mul(..) | Expr::Div(..) | Expr::Mod(..)
+        | Expr::Equal(..) | Expr::NotEqual(..) | Expr::LessThan(..) | Expr::LessThanOrEqual(..)
+        | Expr::GreaterThan(..) | Expr::GreaterThanOrEqual(..) => unreachable!("eval handles these itself"),
+        Expr::Float(f) => Ok(Flow::Continue(Value::Float(*f))),
+        Expr::String(s) => Ok(Flow::Continue(Value::String(s.as_str().into()))),
+        Expr::Bool(b) => Ok(Flow::Continue(Value::Bool(*b))),
+        Expr::Null => Ok(Flow::Continue(Value::Null)),
+
+        Expr::PropertyAccess { object, property } => {
+            let object = value!(eval(object, env)?);
+
+            Ok(Flow::Continue(get_property(object, property, expr.span)?))
         }
 
-        Expr::Sub(left, right) => {
-            let left_value = eval(left, context)?.unwrap();
-            let right_value = eval(right, context)?.unwrap();
+        Expr::Neg(inner) => {
+            let value = value!(eval(inner, env)?);
 
-            match (left_value, right_value) {
-                (Expr::Int(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Int(l - r))),
-                (Expr::Float(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Float(l - r))),
-                (Expr::Int(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Float(l as f64 - r))),
-                (Expr::Float(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Float(l - r as f64))),
-                
-                _ => Err(EvalError {
-                    message: format!("Cannot subtract values: {:?} - {:?}", left.node, right.node),
-                    message_short: "cannot subtract".to_string(),
-                    span: expr.span,
-                }),
-            }
+            Ok(Flow::Continue(negate(value, expr.span)?))
         }
 
         Expr::Identifier(name) => {
-            match context.get(name) {
-                Some(value) => Ok(Flow::Continue(value.clone())),
+            match env.get_global(name) {
+                Some(value) => Ok(Flow::Continue(value)),
                 None => Err(EvalError {
                     message: format!("Undefined variable: {}", name),
                     message_short: "not defined".to_string(),
                     span: expr.span,
-                }),
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                }.into()),
             }
         }
 
         Expr::Range { start, end } => {
-            Ok(Flow::Continue(Expr::Range {
-                start: start.clone(),
-                end: end.clone(),
-            }))
+            Ok(Flow::Continue(Value::Range(
+                range_bound(value!(eval(start, env)?), "start", expr.span)?,
+                range_bound(value!(eval(end, env)?), "end", expr.span)?,
+            )))
         }
 
         Expr::InclusiveRange { start, end } => {
-            Ok(Flow::Continue(Expr::InclusiveRange {
-                start: start.clone(),
-                end: end.clone(),
-            }))
-        }
-
-        Expr::Call { callee, args } => {
-            let mut evaluated_args: Vec<SpannedExpr> = args.iter()
-                .map(|arg| {
-                    match eval(arg, context) {
-                        Ok(v) => Ok(SpannedExpr {
-                            node: v.unwrap(),
-                            span: arg.span,
-                        }),
+            Ok(Flow::Continue(Value::InclusiveRange(
+                range_bound(value!(eval(start, env)?), "start", expr.span)?,
+                range_bound(value!(eval(end, env)?), "end", expr.span)?,
+            )))
+        }
 
-                        Err(e) => Err(e),
-                    }
-                })
-                .collect::<Result<Vec<SpannedExpr>, EvalError>>()?;
-
-            match eval(callee, context)?.unwrap() {
-                Expr::InternalFunction { name, args, func } => {
-                    if args.contains(&"self".to_string()) {
-                        match &callee.node {
-                            Expr::PropertyAccess { object, .. } => {
-                                evaluated_args.insert(0, SpannedExpr {
-                                    node: eval(object, context)?.unwrap(),
-                                    span: object.span,
-                                });
-                            }
-
-                            _ => { }
-                        }
-                    }
+        Expr::Call { callee, args, named } => {
+            call(expr, callee, args, named, env, false)
+        }
 
-                    if !args.contains(&"__args__".to_string()) && args.len() != evaluated_args.len() {
-                        let error_span = if evaluated_args.len() > args.len() {
-                            SimpleSpan::from(
-                                evaluated_args[args.len()].span.start..evaluated_args[evaluated_args.len() - 1].span.end
-                            )
-                        } else {
-                            expr.span
-                        };
-                        
-                        if evaluated_args.len() > args.len() {
-                            return Err(EvalError {
-                                message: format!("Function {} expects {} arguments, got {}", name, args.len(), evaluated_args.len()),
-                                message_short: format!("{} arguments too many", evaluated_args.len() - args.len()),
-                                span: error_span,
-                            });
-                        } else {
-                            return Err(EvalError {
-                                message: format!("Function {} expects {} arguments, got {}", name, args.len(), evaluated_args.len()),
-                                message_short: format!("{} arguments too few", args.len() - evaluated_args.len()),
-                                span: error_span,
-                            });
-                        }
-                    }
+        Expr::Let { name, value, slot, .. } => {
+            let value = value!(eval(value, env)?);
 
-                    match func(evaluated_args) {
-                        Ok(response) => {
-                            if let Some(replace_self) = response.replace_self {
-                                match &callee.node {
-                                    Expr::PropertyAccess { object, property: _ } => {
-                                        if let Expr::Identifier(obj_name) = &object.node {
-                                            context.insert(obj_name.clone(), replace_self);
-                                        }
-                                    }
-                                    
-                                    _ => {}
-                                }
-                            }
-
-                            Ok(Flow::Continue(response.return_value))
-                        },
-                        Err((msg, span)) => Err(EvalError {
-                            message: msg.clone(),
-                            message_short: msg,
-                            span,
-                        }),
-                    }
-                }
+            match slot {
+                Some(slot) => env.set(*slot, value),
+                None => env.set_global(name, value),
+            }
 
-                Expr::Function { name, args, body } => {
-                    if args.len() != evaluated_args.len() {
-                        let error_span = if evaluated_args.len() > args.len() {
-                            SimpleSpan::from(
-                                evaluated_args[args.len()].span.start..evaluated_args[evaluated_args.len() - 1].span.end
-                            )
-                        } else {
-                            expr.span
-                        };
-
-                        if evaluated_args.len() > args.len() {
-                            return Err(EvalError {
-                                message: format!("Function {} expects {} arguments, got {}", name, args.len(), evaluated_args.len()),
-                                message_short: format!("{} arguments too many", evaluated_args.len() - args.len()),
-                                span: error_span,
-                            });
-                        } else {
-                            return Err(EvalError {
-                                message: format!("Function {} expects {} arguments, got {}", name, args.len(), evaluated_args.len()),
-                                message_short: format!("{} arguments too few", args.len() - evaluated_args.len()),
-                                span: error_span,
-                            });
-                        }
-                    }
+            Ok(Flow::Continue(Value::Null))
 
-                    let mut new_context = context.clone();
+        }
 
-                    for (i, arg_name) in args.iter().enumerate() {
-                        new_context.insert(arg_name.clone(), evaluated_args[i].node.clone());
-                    }
+        Expr::Destructure { pattern, value } => {
+            let value = value!(eval(value, env)?);
 
-                    match eval(&*body, &mut new_context)? {
-                        Flow::Continue(v) => Ok(Flow::Continue(v)),
-                        Flow::Return(v) => Ok(Flow::Continue(v)),
-                        Flow::Break => Err(EvalError {
-                            message: "Unexpected break in function".to_string(),
-                            message_short: "unexpected break".to_string(),
-                            span: expr.span,
-                        }),
-                        Flow::Skip => Err(EvalError {
-                            message: "Unexpected skip in function".to_string(),
-                            message_short: "unexpected skip".to_string(),
-                            span: expr.span,
-                        }),
-                    }
+            destructure(pattern, value, env)?;
+
+            Ok(Flow::Continue(Value::Null))
+        }
+
+        Expr::Match { value, arms } => {
+            let subject = value!(eval(value, env)?);
+
+            for arm in arms {
+                if !match_pattern(&arm.pattern, &subject, env)? {
+                    continue;
                 }
 
-                #[cfg(not(target_arch = "wasm32"))]
-                Expr::FFILibrary(library) => {
-                    let result = crate::libraries::ffi::execute_ffi_call(
-                        &library,
-                        match &callee.node {
-                            Expr::PropertyAccess { property, .. } => property,
-                            _ => unreachable!(),
-                        },
-                        evaluated_args,
-                    );
-
-                    match result {
-                        Ok(value) => Ok(Flow::Continue(value)),
-                        Err(msg) => Err(EvalError {
-                            message: msg.clone(),
-                            message_short: msg,
-                            span: expr.span,
-                        }),
-                    }
+                if let Some(guard) = &arm.guard && !condition(value!(eval(guard, env)?), guard.span)? {
+                    continue;
                 }
 
-                v => Err(EvalError {
-                    message: format!("{:?} is not a function", v),
-                    message_short: "not a function".to_string(),
-                    span: expr.span,
-                })
+                return eval(&arm.body, env);
             }
+
+            Err(no_match(&subject, value.span).into())
+        }
+
+        Expr::Assign { target, value } => {
+            let value = value!(eval(value, env)?);
+
+            assign(target, value, env)?;
+
+            Ok(Flow::Continue(Value::Null))
+        }
+
+        Expr::CompoundAssign { op, target, value: right } => {
+            let indexes = target_indexes(target, env)?;
+            let current = read_target(target, &indexes, env)?;
+            let right_value = value!(eval(right, env)?);
+            let value = binary(*op, current, right_value, Span::from(target.span.start..right.span.end), right.span)?;
+
+            write_target(target, &indexes, value, env)?;
+
+            Ok(Flow::Continue(Value::Null))
         }
 
-        Expr::Let { name, value } => {
-            let value = eval(value, context)?.unwrap();
-            context.insert(name.clone(), value);
-            
-            Ok(Flow::Continue(Expr::Null))
+        Expr::Function { name, args, arity, body, slot, frame_size, .. } => {
+            let function = Value::Function(Rc::new(Function::new(name.clone(), args.clone(), *arity, body.clone(), *frame_size, env.clone())));
+
+            match slot {
+                Some(slot) => env.set(*slot, function),
+                None => env.set_global(name, function),
+            }
 
+            Ok(Flow::Continue(Value::Null))
         }
 
-        Expr::Function { name, args, body } => {
-            context.insert(name.clone(), Expr::Function {
-                name: name.clone(),
-                args: args.clone(),
-                body: body.clone(),
+        Expr::Struct { name, fields, methods, slot } => {
+            let methods = methods.iter().map(|method| {
+                let function = Function::new(format!("{}.{}", name, method.name), method.args.clone(), method.arity, method.body.clone(), method.frame_size, env.clone());
+                (method.name.clone(), function)
             });
 
-            Ok(Flow::Continue(Expr::Null))
+            bind(name, slot, make_struct(name, fields, methods), env);
+
+            Ok(Flow::Continue(Value::Null))
         }
 
-        Expr::Block(exprs) => {
-            let preexisting_keys = context.keys().cloned().collect::<Vec<String>>();
+        Expr::Enum { name, variants, slot } => {
+            bind(name, slot, make_enum(name, variants), env);
 
-            for e in exprs {
-                match eval(e, context)? {
-                    Flow::Continue(_) => {},
-                    Flow::Return(v) => return Ok(Flow::Return(v)),
-                    Flow::Break => return Ok(Flow::Break),
-                    Flow::Skip => return Ok(Flow::Skip),
-                }
+            Ok(Flow::Continue(Value::Null))
+        }
+
+        Expr::Lambda { args, arity, body, frame_size, .. } => {
+            Ok(Flow::Continue(Value::Function(Rc::new(Function::new("<anonymous>".to_string(), args.clone(), *arity, body.clone(), *frame_size, env.clone())))))
+        }
+
+        Expr::Default { index, value, .. } => {
+            if !env.was_passed(*index) {
+                let value = value!(eval(value, env)?);
+                env.set(Slot { depth: 0, index: *index }, value);
             }
 
-            for key in context.keys().cloned().collect::<Vec<String>>() {
-                if !preexisting_keys.contains(&key) {
-                    context.remove(&key);
+            Ok(Flow::Continue(Value::Null))
+        }
+
+        Expr::Block { statements, value } => {
+            for e in statements {
+                match eval(e, env)? {
+                    Flow::Continue(_) => {},
+                    flow => return Ok(flow),
                 }
             }
 
-            Ok(Flow::Continue(Expr::Null))
+            match value {
+                Some(value) => eval(value, env),
+                None => Ok(Flow::Continue(Value::Null)),
+            }
         }
 
-        Expr::InfiniteLoop { body } => {
+        Expr::InfiniteLoop { body, label } => {
             loop {
-                match eval(body, context)? {
+                match eval(body, env)? {
                     Flow::Continue(_) => {},
                     Flow::Return(v) => return Ok(Flow::Return(v)),
-                    Flow::Break => return Ok(Flow::Continue(Expr::Null)),
-                    Flow::Skip => continue,
+                    Flow::Break(target) if targets_loop(&target, label) => return Ok(Flow::Continue(Value::Null)),
+                    Flow::Skip(target) if targets_loop(&target, label) => continue,
+                    flow => return Ok(flow),
                 }
             }
         }
 
-        Expr::ForLoop { iterator_name, iterator_range, body } => {
-            let range_value = eval(iterator_range, context)?.unwrap();
-
-            match range_value {
-                Expr::Range { start, end } => {
-                    let start = match eval(&start, context)?.unwrap() {
-                        Expr::Int(n) => n,
-
-                        _ => {
-                            return Err(EvalError {
-                                message: format!("Range start must be an integer, got {:?}", start),
-                                message_short: "invalid range start".to_string(),
-                                span: expr.span,
-                            });
-                        }
-                    };
+        Expr::WhileLoop { condition, body, label } => {
+            while eval_condition(condition, env)? {
+                match eval(body, env)? {
+                    Flow::Continue(_) => {},
+                    Flow::Return(v) => return Ok(Flow::Return(v)),
+                    Flow::Break(target) if targets_loop(&target, label) => break,
+                    Flow::Skip(target) if targets_loop(&target, label) => continue,
+                    flow => return Ok(flow),
+                }
+            }
 
-                    let end = match eval(&end, context)?.unwrap() {
-                        Expr::Int(n) => n,
+            Ok(Flow::Continue(Value::Null))
+        }
 
-                        _ => {
-                            return Err(EvalError {
-                                message: format!("Range end must be an integer, got {:?}", end),
-                                message_short: "invalid range end".to_string(),
-                                span: expr.span,
-                            });
-                        }
-                    };
+        Expr::ForLoop { iterator_name, index_name, iterator_slot, index_slot, iterator_range, body, label, scope_size } => {
+            let iterable = value!(eval(iterator_range, env)?);
+
+            for (i, value) in iterate(iterable, iterator_range.span)?.enumerate() {
+                let value = value.map_err(|msg| EvalError {
+                    message: msg.clone(),
+                    message_short: msg,
+                    span: iterator_range.span,
+                    source: None,
+                    trace: vec![],
+                    thrown: None,
+                })?;
 
-                    for i in start..end {
-                        context.insert(iterator_name.clone(), Expr::Int(i));
+                let iteration = scope_size.map(|size| env.call(size, 0, vec![]));
+                let env = iteration.as_ref().unwrap_or(env);
 
-                        match eval(body, context)? {
-                            Flow::Continue(_) => {},
-                            Flow::Return(v) => return Ok(Flow::Return(v)),
-                            Flow::Break => break,
-                            Flow::Skip => continue,
-                        }
+                if let Some(index_name) = index_name {
+                    match index_slot {
+                        Some(slot) => env.set(*slot, Value::Int(i as i64)),
+                        None => env.set_global(index_name, Value::Int(i as i64)),
                     }
+                }
 
-                    Ok(Flow::Continue(Expr::Null))
+                match iterator_slot {
+                    Some(slot) => env.set(*slot, value),
+                    None => env.set_global(iterator_name, value),
                 }
 
-                Expr::InclusiveRange { start, end } => {
-                    let start = match eval(&start, context)?.unwrap() {
-                        Expr::Int(n) => n,
+                match eval(body, env)? {
+                    Flow::Continue(_) => {},
+                    Flow::Return(v) => return Ok(Flow::Return(v)),
+                    Flow::Break(target) if targets_loop(&target, label) => break,
+                    Flow::Skip(target) if targets_loop(&target, label) => continue,
+                    flow => return Ok(flow),
+                }
+            }
 
-                        _ => {
-                            return Err(EvalError {
-                                message: format!("Range start must be an integer, got {:?}", start),
-                                message_short: "invalid range start".to_string(),
-                                span: expr.span,
-                            });
-                        }
-                    };
+            Ok(Flow::Continue(Value::Null))
+        }
 
-                    let end = match eval(&end, context)?.unwrap() {
-                        Expr::Int(n) => n,
+        Expr::Throw(value) => {
+            let value = value!(eval(value, env)?);
+            Err(throw(value, expr.span).into())
+        }
 
-                        _ => {
-                            return Err(EvalError {
-                                message: format!("Range end must be an integer, got {:?}", end),
-                                message_short: "invalid range end".to_string(),
-                                span: expr.span,
-                            });
-                        }
-                    };
+        Expr::Try { body, catch_slot, catch_body, finally_body, .. } => {
+            // a tail call has to happen inside the try, so errors from it are caught
+            let mut result = eval(body, env).and_then(|flow| finish_tail_call(flow, env));
 
-                    for i in start..=end {
-                        context.insert(iterator_name.clone(), Expr::Int(i));
+            if let Err(error) = &result && let Some(catch_body) = catch_body {
+                if let Some(slot) = catch_slot {
+                    env.set(*slot, error_value(error, &env.source));
+                }
 
-                        match eval(body, context)? {
-                            Flow::Continue(_) => {},
-                            Flow::Return(v) => return Ok(Flow::Return(v)),
-                            Flow::Break => break,
-                            Flow::Skip => continue,
-                        }
-                    }
+                result = eval(catch_body, env).and_then(|flow| finish_tail_call(flow, env));
+            }
 
-                    Ok(Flow::Continue(Expr::Null))
+            // finally runs whatever happened above, and a return or break in it wins
+            if let Some(finally_body) = finally_body {
+                match eval(finally_body, env)? {
+                    Flow::Continue(_) => {}
+                    flow => return Ok(flow),
                 }
+            }
 
-                _ => Err(EvalError {
-                    message: format!("Cannot iterate over value: {:?}", range_value),
-                    message_short: "cannot iterate".to_string(),
-                    span: expr.span,
-                }),
+            match result? {
+                Flow::Continue(_) => Ok(Flow::Continue(Value::Null)),
+                flow => Ok(flow),
             }
         }
 
         Expr::Return(value) => {
-            let return_value = eval(value, context)?.unwrap();
+            if let Expr::Call { callee, args, named } = &value.node {
+                return match call(value, callee, args, named, env, true)? {
+                    Flow::Continue(v) => Ok(Flow::Return(v)),
+                    flow => Ok(flow),
+                };
+            }
+
+            let return_value = value!(eval(value, env)?);
             Ok(Flow::Return(return_value))
         }
 
-        Expr::Break => {
-            Ok(Flow::Break)
-        },
-
-        Expr::Continue => {
-            Ok(Flow::Skip)
+        Expr::Break(label) => {
+            Ok(Flow::Break(label.clone()))
         },
 
-        Expr::Equal(left, right) => {
-            let left_value = eval(left, context)?.unwrap();
-            let right_value = eval(right, context)?.unwrap();
-
-            match (left_value, right_value) {
-                (Expr::Int(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Bool(l == r))),
-                (Expr::Float(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Bool(l == r))),
-                (Expr::Int(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Bool((l as f64) == r))),
-                (Expr::Float(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Bool(l == (r as f64)))),
-                (Expr::Bool(l), Expr::Bool(r)) => Ok(Flow::Continue(Expr::Bool(l == r))),
-                (Expr::String(l), Expr::String(r)) => Ok(Flow::Continue(Expr::Bool(l == r))),
-                (Expr::Null, Expr::Null) => Ok(Flow::Continue(Expr::Bool(true))),
-
-                _ => Ok(Flow::Continue(Expr::Bool(false))),
-            }
+        Expr::Continue(label) => {
+            Ok(Flow::Skip(label.clone()))
         },
 
-        Expr::NotEqual(left, right) => {
-            let left_value = eval(left, context)?.unwrap();
-            let right_value = eval(right, context)?.unwrap();
-
-            match (left_value, right_value) {
-                (Expr::Int(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Bool(l != r))),
-                (Expr::Float(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Bool(l != r))),
-                (Expr::Int(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Bool((l as f64) != r))),
-                (Expr::Float(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Bool(l != (r as f64)))),
-                (Expr::Bool(l), Expr::Bool(r)) => Ok(Flow::Continue(Expr::Bool(l != r))),
-                (Expr::String(l), Expr::String(r)) => Ok(Flow::Continue(Expr::Bool(l != r))),
-                (Expr::Null, Expr::Null) => Ok(Flow::Continue(Expr::Bool(false))),
-
-                _ => Ok(Flow::Continue(Expr::Bool(true))),
+        Expr::And(left, right) => {
+            if !eval_condition(left, env)? {
+                return Ok(Flow::Continue(Value::Bool(false)));
             }
-        },
-
-        Expr::LessThan(left, right) => {
-            let left_value = eval(left, context)?.unwrap();
-            let right_value = eval(right, context)?.unwrap();
 
-            match (left_value, right_value) {
-                (Expr::Int(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Bool(l < r))),
-                (Expr::Float(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Bool(l < r))),
-                (Expr::Int(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Bool((l as f64) < r))),
-                (Expr::Float(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Bool(l < (r as f64)))),
-
-                _ => Err(EvalError {
-                    message: format!("Cannot compare values: {:?} < {:?}", left.node, right.node),
-                    message_short: "cannot compare".to_string(),
-                    span: expr.span,
-                }),
-            }
+            Ok(Flow::Continue(Value::Bool(eval_condition(right, env)?)))
         },
 
-        Expr::LessThanOrEqual(left, right) => {
-            let left_value = eval(left, context)?.unwrap(); 
-            let right_value = eval(right, context)?.unwrap();
-
-            match (left_value, right_value) {
-                (Expr::Int(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Bool(l <= r))),
-                (Expr::Float(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Bool(l <= r))),
-                (Expr::Int(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Bool((l as f64) <= r))),
-                (Expr::Float(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Bool(l <= (r as f64)))),
-
-                _ => Err(EvalError {
-                    message: format!("Cannot compare values: {:?} <= {:?}", left.node, right.node),
-                    message_short: "cannot compare".to_string(),
-                    span: expr.span,
-                }),
+        Expr::Or(left, right) => {
+            if eval_condition(left, env)? {
+                return Ok(Flow::Continue(Value::Bool(true)));
             }
-        },
-
-        Expr::GreaterThan(left, right) => {
-            let left_value = eval(left, context)?.unwrap();
-            let right_value = eval(right, context)?.unwrap();
 
-            match (left_value, right_value) {
-                (Expr::Int(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Bool(l > r))),
-                (Expr::Float(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Bool(l > r))),
-                (Expr::Int(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Bool((l as f64) > r))),
-                (Expr::Float(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Bool(l > (r as f64)))),
-
-                _ => Err(EvalError {
-                    message: format!("Cannot compare values: {:?} > {:?}", left.node, right.node),
-                    message_short: "cannot compare".to_string(),
-                    span: expr.span,
-                }),
-            }
+            Ok(Flow::Continue(Value::Bool(eval_condition(right, env)?)))
         },
 
-        Expr::GreaterThanOrEqual(left, right) => {
-            let left_value = eval(left, context)?.unwrap();
-            let right_value = eval(right, context)?.unwrap();
-
-            match (left_value, right_value) {
-                (Expr::Int(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Bool(l >= r))),
-                (Expr::Float(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Bool(l >= r))),
-                (Expr::Int(l), Expr::Float(r)) => Ok(Flow::Continue(Expr::Bool((l as f64) >= r))),
-                (Expr::Float(l), Expr::Int(r)) => Ok(Flow::Continue(Expr::Bool(l >= (r as f64)))),
-
-                _ => Err(EvalError {
-                    message: format!("Cannot compare values: {:?} >= {:?}", left.node, right.node),
-                    message_short: "cannot compare".to_string(),
-                    span: expr.span,
-                }),
-            }
+        Expr::Not(inner) => {
+            Ok(Flow::Continue(Value::Bool(!eval_condition(inner, env)?)))
         },
 
-        Expr::If { condition, then_branch, else_branch } => {
-            let condition_value = eval(condition, context)?.unwrap();
-
-            match condition_value {
-                Expr::Bool(true) => eval(then_branch, context),
-                Expr::Bool(false) | Expr::Null => {
-                    if let Some(else_branch) = else_branch {
-                        eval(else_branch, context)
-                    } else {
-                        Ok(Flow::Continue(Expr::Null))
-                    }
-                },
-
-                _ => Err(EvalError {
-                    message: format!("Condition must be a boolean, got {:?}", condition_value),
-                    message_short: "invalid condition".to_string(),
-                    span: expr.span,
-                }),
+        Expr::If { condition: if_condition, then_branch, else_branch } => {
+            if condition(value!(eval(if_condition, env)?), expr.span)? {
+                eval(then_branch, env)
+            } else if let Some(else_branch) = else_branch {
+                eval(else_branch, env)
+            } else {
+                Ok(Flow::Continue(Value::Null))
             }
         }
 
         Expr::Import { name, import_as } => {
-            let import_as = match import_as {
-                Some(as_name) => as_name.clone(),
-                None => name.clone(),
-            };
-
-            let mut path: std::path::PathBuf = std::path::PathBuf::new();
-
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                path = std::env::current_dir().unwrap();
-                
-                let sys_args = std::env::args().collect::<Vec<String>>();
-                if sys_args.len() > 2 && sys_args[1] == "run" {
-                    path.push(&sys_args[2]);
-                    path.pop();
-                }
-
-                if context.contains_key("CURRENTLY_PARSING_MODULE_PATH") {
-                    if let Expr::String(current_module_path) = context.get("CURRENTLY_PARSING_MODULE_PATH").unwrap() {
-                        let mut module_path = std::path::PathBuf::from(current_module_path);
-                        module_path.pop();
-                        path = module_path;
-                    }
-                }
-
-                if context.contains_key("CURRENTLY_PARSING_PACKAGE_NAME") {
-                    if let Expr::String(current_package_name) = context.get("CURRENTLY_PARSING_PACKAGE_NAME").unwrap() {
-                        path.push(".modu");
-                        path.push("packages");
-                        path.push(current_package_name);
-                    }
-                }
-            }
-
-            if name.ends_with(".modu") {
-                path.push(name);
-                
-                let source = std::fs::read_to_string(path.clone()).map_err(|e| EvalError {
-                    message: format!("Failed to read module file {}: {}", name, e),
-                    message_short: "failed to read module".to_string(),
-                    span: expr.span,
-                })?;
-
-                let mut new_context = crate::utils::create_context();
-                new_context.insert(
-                    "CURRENTLY_PARSING_MODULE_PATH".to_string(),
-                    Expr::String(path.to_str().unwrap().to_string())
-                );
-
-                crate::parser::parse(&source, path.to_str().unwrap(), &mut new_context);
-
-                if import_as == "*" {
-                    for (k, v) in new_context {
-                        context.insert(k, v);
-                    }
-                } else {
-                    let mut symbols = HashMap::new();
-
-                    for (k, v) in new_context.iter().filter(|(k, _)| !crate::utils::create_context().contains_key(*k)) {
-                        symbols.insert(k.clone(), SpannedExpr {
-                            node: v.clone(),
-                            span: expr.span,
-                        });
-                    }
-
-                    context.insert(import_as.clone().replace(".modu", ""), Expr::Module {
-                        symbols,
-                    });
-                }
-            } else {
-                match crate::libraries::get_package(name) {
-                    Some(module) => {
-                        if import_as == "*" {
-                            if let Expr::Module { symbols } = module {
-                                for (k, v) in symbols {
-                                    context.insert(k, v.node);
-                                }
-                            } else {
-                                return Err(EvalError {
-                                    message: format!("Package {} is not a module", name),
-                                    message_short: "not a module".to_string(),
-                                    span: expr.span,
-                                });
-                            }
-                        } else {
-                            context.insert(import_as.clone(), module);
-                        }
-                    }
-
-                    #[cfg(not(target_arch = "wasm32"))]
-                    None => {
-                        path.push(".modu");
-                        path.push("packages");
-                        path.push(name);
-                        path.push("lib.modu");
-
-                        if !path.exists() {
-                            return Err(EvalError {
-                                message: format!("Package {} does not exist or is not installed", name),
-                                message_short: "package not found".to_string(),
-                                span: expr.span,
-                            });
-                        }
-
-                        let source = std::fs::read_to_string(path.clone()).map_err(|e| EvalError {
-                            message: format!("Failed to read module file for package {}: {}", name, e),
-                            message_short: "failed to read module".to_string(),
-                            span: expr.span,
-                        })?;
-
-                        let mut new_context = crate::utils::create_context();
-                        new_context.insert(
-                            "CURRENTLY_PARSING_PACKAGE_PATH".to_string(),
-                            Expr::String(path.to_str().unwrap().to_string())
-                        );
-                        new_context.insert(
-                            "CURRENTLY_PARSING_PACKAGE_NAME".to_string(),
-                            Expr::String(name.clone()),
-                        );
-
-                        crate::parser::parse(&source, path.to_str().unwrap(), &mut new_context);
-
-                        if import_as == "*" {
-                            for (k, v) in new_context {
-                                context.insert(k, v);
-                            }
-                        } else {
-                            let mut symbols = HashMap::new();
-
-                            for (k, v) in new_context.iter().filter(|(k, _)| !crate::utils::create_context().contains_key(*k)) {
-                                symbols.insert(k.clone(), SpannedExpr {
-                                    node: v.clone(),
-                                    span: expr.span,
-                                });
-                            }
-
-                            context.insert(import_as.clone().replace(".modu", ""), Expr::Module {
-                                symbols,
-                            });
-                        }
-                    }
-
-                    #[cfg(target_arch = "wasm32")]
-                    None => {
-                        return Err(EvalError {
-                            message: format!("Could not find package {}", name),
-                            message_short: "package not found".to_string(),
-                            span: expr.span,
-                        });
-                    }
-                }
-
-               
-            }
-
-            Ok(Flow::Continue(Expr::Null))
+            Ok(Flow::Continue(import(name, import_as, expr.span, env)?))
         }
 
         Expr::Array(elements) => {
             let mut evaluated_elements = Vec::new();
 
             for element in elements {
-                let value = eval(element, context)?.unwrap();
-                evaluated_elements.push(SpannedExpr {
-                    node: value,
-                    span: element.span,
-                });
+                evaluated_elements.push(value!(eval(element, env)?));
             }
 
-            Ok(Flow::Continue(Expr::Array(evaluated_elements)))
+            Ok(Flow::Continue(Value::Array(Rc::new(evaluated_elements))))
         }
 
-        Expr::IndexAccess { object, index } => {
-            let object_value = eval(object, context)?.unwrap();
-            let index_value = eval(index, context)?.unwrap();
-            
-            match (object_value, index_value) {
-                (Expr::Array(elements), Expr::Int(i)) => {
-                    let idx = if i < 0 {
-                        elements.len() as i64 + i
-                    } else {
-                        i
-                    };
+        Expr::Interpolation(parts) => {
+            let mut values = Vec::with_capacity(parts.len());
 
-                    if idx < 0 || idx >= elements.len() as i64 {
-                        return Err(EvalError {
-                            message: format!("Array index out of bounds: {}", i),
-                            message_short: "index out of bounds".to_string(),
-                            span: expr.span,
-                        });
-                    }
+            for part in parts {
+                values.push(value!(eval(part, env)?));
+            }
 
-                    Ok(Flow::Continue(elements[idx as usize].node.clone()))
-                }
+            Ok(Flow::Continue(interpolate(values)))
+        }
 
-                (Expr::Object { properties }, Expr::String(key)) => {
-                    match properties.get(&key) {
-                        Some(value) => Ok(Flow::Continue(value.clone())),
-                        None => Err(EvalError {
-                            message: format!("Object has no property named {}", key),
-                            message_short: "no such property".to_string(),
-                            span: expr.span,
-                        }),
-                    }
-                }
+        Expr::ObjectLiteral { properties } => {
+            let mut evaluated_properties = HashMap::new();
 
-                (v, _) => Err(EvalError {
-                    message: format!("Cannot index into value: {:?}", v),
-                    message_short: "cannot index".to_string(),
-                    span: expr.span,
-                }),
+            for (key, value) in properties {
+                let key = object_key(value!(eval(key, env)?), key.span)?;
+                let value = value!(eval(value, env)?);
+                evaluated_properties.insert(key, value);
             }
+
+            Ok(Flow::Continue(Value::Object(Rc::new(evaluated_properties))))
         }
 
-        v => {
-            Err(EvalError {
-                message: format!("No evaluator for {:?}", v),
-                message_short: "couldn't evaluate".to_string(),
-                span: expr.span,
-            })
+        Expr::IndexAccess { object, index } => {
+            let object_value = value!(eval(object, env)?);
+            let index_value = value!(eval(index, env)?);
+
+            Ok(Flow::Continue(get_index(object_value, index_value, expr.span)?))
         }
     }
-}
\ No newline at end of file
+}
@@ -9,6 +9,8 @@ pub enum LexingError {
     UnexpectedToken,
     InvalidInteger(String),
     InvalidFloat(String),
+    InvalidInterpolation(String),
+    InvalidString(String),
 }
 
 impl From<std::num::ParseIntError> for LexingError {
@@ -39,23 +41,56 @@ pub enum Token {
     #[token("=")]
     Assign,
 
+    #[token("+=")]
+    PlusAssign,
+
+    #[token("-=")]
+    MinusAssign,
+
+    #[token("*=")]
+    StarAssign,
+
+    #[token("/=")]
+    SlashAssign,
+
+    #[token("%=")]
+    PercentAssign,
+
     #[token(";")]
     Semicolon,
 
     #[regex("[a-zA-Z_][a-zA-Z0-9_]*", |lex| lex.slice().to_string())]
     Identifier(String),
 
-    #[regex("[0-9]+", |lex| lex.slice().parse::<i64>())]
+    // underscores can be used to group digits, like 1_000_000
+    #[regex("[0-9][0-9_]*", |lex| lex.slice().replace('_', "").parse::<i64>())]
+    #[regex("0[xX][0-9a-zA-Z_]*", |lex| radix(lex.slice(), 16, "hex"))]
+    #[regex("0[bB][0-9a-zA-Z_]*", |lex| radix(lex.slice(), 2, "binary"))]
+    #[regex("0[oO][0-9a-zA-Z_]*", |lex| radix(lex.slice(), 8, "octal"))]
     Int(i64),
 
-    #[regex(r"[0-9]+\.[0-9]+", |lex| lex.slice().parse::<f64>())]
+    // 1.5, .5, 1e-9 and 1.5e3
+    #[regex(r"[0-9][0-9_]*\.[0-9][0-9_]*([eE][+-]?[0-9][0-9_]*)?", float)]
+    #[regex(r"\.[0-9][0-9_]*([eE][+-]?[0-9][0-9_]*)?", float)]
+    #[regex(r"[0-9][0-9_]*[eE][+-]?[0-9][0-9_]*", float)]
     Float(f64),
 
-    #[regex(r#""([^"\\]|\\.)*""#, |lex| {
-        lex.slice()[1..lex.slice().len()-1].to_string()
-    })]
+    // the escapes are replaced here, so the string holds the actual characters
+    #[regex(r#""([^"\\]|\\.)*""#, |lex| unescape(&lex.slice()[1..lex.slice().len()-1]))]
+    // r"..." keeps backslashes as they are, for regexes and windows paths
+    #[regex(r#"r"[^"]*""#, |lex| lex.slice()[2..lex.slice().len()-1].to_string())]
+    #[token(r#"""""#, |lex| multiline(lex, false), priority = 10)]
+    #[token(r#"r""""#, |lex| multiline(lex, true), priority = 10)]
     String(String),
 
+    // f"...", only finds where it ends, lex splits it into the tokens below
+    #[token("f\"", fstring)]
+    FString(String),
+
+    // f"a {b} c" is lexed as FStringStart String("a ") LBrace <tokens of b> RBrace String(" c") FStringEnd
+    FStringStart,
+    FStringEnd,
+
     #[regex("true|false", |lex| lex.slice() == "true")]
     Bool(bool),
 
@@ -92,12 +127,53 @@ pub enum Token {
     #[token("for")]
     For,
 
+    #[token("while")]
+    While,
+
+    #[token("throw")]
+    Throw,
+
+    #[token("try")]
+    Try,
+
+    #[token("catch")]
+    Catch,
+
+    #[token("finally")]
+    Finally,
+
+    #[token("match")]
+    Match,
+
+    // both words mean the same thing
+    #[token("struct")]
+    #[token("class")]
+    Struct,
+
+    #[token("enum")]
+    Enum,
+
+    #[token("=>")]
+    FatArrow,
+
+    #[token("->")]
+    Arrow,
+
+    #[token("|")]
+    Pipe,
+
+    #[regex("'[a-zA-Z_][a-zA-Z0-9_]*", |lex| lex.slice()[1..].to_string())]
+    Label(String),
+
     #[token(",")]
     Comma,
 
     #[token(".")]
     Dot,
 
+    #[token(":")]
+    Colon,
+
     #[token("*")]
     Star,
     
@@ -107,6 +183,12 @@ pub enum Token {
     #[token("-")]
     Minus,
 
+    #[token("/")]
+    Slash,
+
+    #[token("%")]
+    Percent,
+
     #[token("(")]
     LParen,
 
@@ -131,6 +213,9 @@ pub enum Token {
     #[token("..=")]
     InclusiveRange,
 
+    #[token("...")]
+    Ellipsis,
+
     #[token("==")]
     DoubleEqual,
 
@@ -149,6 +234,15 @@ pub enum Token {
     #[token(">=")]
     GreaterThanOrEqual,
 
+    #[token("&&")]
+    And,
+
+    #[token("||")]
+    Or,
+
+    #[token("!")]
+    Bang,
+
     #[regex(r"[ \t\n\f\r]+", logos::skip)]
     Whitespace,
 
@@ -159,6 +253,295 @@ pub enum Token {
     MultiLineComment,
 }
 
+// turns \n, \t, \u{1F600} and the other escapes into the characters they stand for
+// \x escapes are bytes, a few of them in a row can make up one utf-8 character
+// bytes that arent valid utf-8 are each read as latin-1, so "\xe9" is still é
+fn unescape(text: &str) -> Result<String, LexingError> {
+    let mut result = String::with_capacity(text.len());
+    let mut bytes: Vec<u8> = vec![];
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+
+        let Some(next) = chars.next() else {
+            result.push('\\');
+            break;
+        };
+
+        if next == 'x' {
+            let hex: String = chars.by_ref().take(2).collect();
+
+            match u8::from_str_radix(&hex, 16) {
+                Ok(byte) if hex.len() == 2 => bytes.push(byte),
+                _ => return Err(LexingError::InvalidString(format!("\\x{} is not a valid byte, it needs two hex digits", hex))),
+            }
+
+            if matches!(chars.peek(), Some('\\')) && chars.clone().nth(1) == Some('x') {
+                continue;
+            }
+
+            for chunk in std::mem::take(&mut bytes).utf8_chunks() {
+                result.push_str(chunk.valid());
+                result.extend(chunk.invalid().iter().map(|byte| char::from(*byte)));
+            }
+
+            continue;
+        }
+
+        match next {
+            'n' => result.push('\n'),
+            't' => result.push('\t'),
+            'r' => result.push('\r'),
+            '0' => result.push('\0'),
+            '"' => result.push('"'),
+            '\\' => result.push('\\'),
+
+            'u' => {
+                if chars.next() != Some('{') {
+                    return Err(LexingError::InvalidString("unicode escapes are written like \\u{1F600}".to_string()));
+                }
+
+                let hex: String = chars.by_ref().take_while(|c| *c != '}').collect();
+
+                match u32::from_str_radix(&hex, 16).ok().filter(|_| hex.len() <= 6).and_then(char::from_u32) {
+                    Some(c) => result.push(c),
+                    None => return Err(LexingError::InvalidString(format!("\\u{{{}}} is not a valid unicode character", hex))),
+                }
+            }
+
+            // anything else is kept as it was written
+            _ => {
+                result.push('\\');
+                result.push(next);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+// """ strings can span multiple lines, the indentation all lines share is removed
+// and so are the line breaks right after the opening and before the closing quotes
+fn multiline(lex: &mut logos::Lexer<Token>, raw: bool) -> Result<String, LexingError> {
+    let rest = lex.remainder();
+    let mut chars = rest.char_indices();
+    let mut end = None;
+
+    while let Some((i, ch)) = chars.next() {
+        if ch == '\\' && !raw {
+            chars.next();
+        } else if rest[i..].starts_with("\"\"\"") {
+            end = Some(i);
+            break;
+        }
+    }
+
+    let Some(end) = end else {
+        lex.bump(rest.len());
+        return Err(LexingError::InvalidString("the string is never closed".to_string()));
+    };
+
+    lex.bump(end + 3);
+
+    let text = rest[..end].strip_prefix('\n').or_else(|| rest[..end].strip_prefix("\r\n")).unwrap_or(&rest[..end]);
+    let mut lines: Vec<&str> = text.split('\n').map(|line| line.trim_end_matches('\r')).collect();
+
+    if lines.len() > 1 && lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+
+    let indent = lines.iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start_matches([' ', '\t']).len())
+        .min()
+        .unwrap_or(0);
+
+    let text = lines.iter()
+        .map(|line| line.get(indent..).unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if raw { Ok(text) } else { unescape(&text) }
+}
+
+// 0xFF, 0b1010 and 0o17, the letters after the prefix are matched too so 0xFG is
+// an invalid hex literal instead of the integer 0xF followed by the identifier G
+fn radix(slice: &str, radix: u32, name: &str) -> Result<i64, LexingError> {
+    let digits = slice[2..].replace('_', "");
+
+    if digits.is_empty() {
+        return Err(LexingError::InvalidInteger(format!("{} literal has no digits", name)));
+    }
+
+    if let Some(digit) = digits.chars().find(|c| !c.is_digit(radix)) {
+        return Err(LexingError::InvalidInteger(format!("{} is not a valid {} digit", digit, name)));
+    }
+
+    Ok(i64::from_str_radix(&digits, radix)?)
+}
+
+fn float(lex: &mut logos::Lexer<Token>) -> Result<f64, LexingError> {
+    let value = lex.slice().replace('_', "").parse::<f64>()?;
+
+    if value.is_infinite() {
+        return Err(LexingError::InvalidFloat("Float literal out of range".to_string()));
+    }
+
+    Ok(value)
+}
+
+// skips to the closing quote of an f-string, the braces have to match and can contain
+// strings with quotes of their own, returns what is between the quotes
+fn fstring(lex: &mut logos::Lexer<Token>) -> Result<String, LexingError> {
+    let rest = lex.remainder();
+    let mut chars = rest.char_indices().peekable();
+    let mut depth = 0;
+
+    while let Some((i, ch)) = chars.next() {
+        match ch {
+            // the braces of \u{...} are part of the escape, not an interpolation
+            '\\' => {
+                if matches!(chars.next(), Some((_, 'u'))) && matches!(chars.peek(), Some((_, '{'))) {
+                    chars.find(|(_, ch)| *ch == '}');
+                }
+            }
+
+            '"' if depth == 0 => {
+                lex.bump(i + 1);
+                return Ok(rest[..i].to_string());
+            }
+
+            // a string inside the braces
+            '"' => {
+                while let Some((_, ch)) = chars.next() {
+                    match ch {
+                        '\\' => { chars.next(); }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+
+            '{' if depth == 0 && matches!(chars.peek(), Some((_, '{'))) => { chars.next(); }
+            '}' if depth == 0 && matches!(chars.peek(), Some((_, '}'))) => { chars.next(); }
+            '}' if depth == 0 => {
+                lex.bump(i + 1);
+                return Err(LexingError::InvalidInterpolation("a single } has to be written as }}".to_string()));
+            }
+
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    lex.bump(rest.len());
+    Err(LexingError::InvalidInterpolation("the string is never closed".to_string()))
+}
+
+// the tokens for the inside of an f-string, start is where the text starts in the input
+fn interpolation(text: &str, start: usize, tokens: &mut Vec<(Token, Span)>) -> Result<(), (LexingError, Span)> {
+    let mut literal = String::new();
+    let mut literal_start = start;
+    let mut chars = text.char_indices().peekable();
+
+    let flush = |literal: &mut String, literal_start: usize, end: usize, tokens: &mut Vec<(Token, Span)>| {
+        if !literal.is_empty() {
+            let span = Span::from(literal_start..end);
+            let text = unescape(&std::mem::take(literal)).map_err(|e| (e, span))?;
+
+            tokens.push((Token::String(text), span));
+        }
+
+        Ok(())
+    };
+
+    while let Some((i, ch)) = chars.next() {
+        match ch {
+            '\\' => {
+                literal.push(ch);
+
+                if let Some((_, next)) = chars.next() {
+                    literal.push(next);
+
+                    if next == 'u' && matches!(chars.peek(), Some((_, '{'))) {
+                        for (_, ch) in chars.by_ref() {
+                            literal.push(ch);
+
+                            if ch == '}' {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+
+            '{' if matches!(chars.peek(), Some((_, '{'))) => {
+                chars.next();
+                literal.push('{');
+            }
+
+            '}' if matches!(chars.peek(), Some((_, '}'))) => {
+                chars.next();
+                literal.push('}');
+            }
+
+            '{' => {
+                flush(&mut literal, literal_start, start + i, tokens)?;
+
+                // fstring already checked that the braces match
+                let mut depth = 1;
+                let mut end = i + 1;
+
+                while let Some((j, ch)) = chars.next() {
+                    match ch {
+                        '"' => {
+                            while let Some((_, ch)) = chars.next() {
+                                match ch {
+                                    '\\' => { chars.next(); }
+                                    '"' => break,
+                                    _ => {}
+                                }
+                            }
+                        }
+
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+
+                    if depth == 0 {
+                        end = j;
+                        break;
+                    }
+                }
+
+                let inner = lex(&text[i + 1..end]).map_err(|(e, span)| (e, Span::from(start + i + 1 + span.start..start + i + 1 + span.end)))?;
+
+                if inner.is_empty() {
+                    return Err((LexingError::InvalidInterpolation("nothing between the braces".to_string()), Span::from(start + i..start + end + 1)));
+                }
+
+                tokens.push((Token::LBrace, Span::from(start + i..start + i + 1)));
+                tokens.extend(inner.into_iter().map(|(token, span)| (token, Span::from(start + i + 1 + span.start..start + i + 1 + span.end))));
+                tokens.push((Token::RBrace, Span::from(start + end..start + end + 1)));
+
+                literal_start = start + end + 1;
+            }
+
+            _ => literal.push(ch),
+        }
+    }
+
+    flush(&mut literal, literal_start, start + text.len(), tokens)?;
+
+    Ok(())
+}
+
 pub fn lex(input: &str) -> Result<Vec<(Token, Span)>, (LexingError, Span)> {
     let mut lexer = Token::lexer(input);
     let mut tokens: Vec<(Token, Span)> = Vec::new();
@@ -167,6 +550,14 @@ pub fn lex(input: &str) -> Result<Vec<(Token, Span)>, (LexingError, Span)> {
         match token {
             Err(e) => return Err((e, SimpleSpan::from(lexer.span()))),
 
+            Ok(Token::FString(text)) => {
+                let span = lexer.span();
+
+                tokens.push((Token::FStringStart, Span::from(span.start..span.start + 2)));
+                interpolation(&text, span.start + 2, &mut tokens)?;
+                tokens.push((Token::FStringEnd, Span::from(span.end - 1..span.end)));
+            }
+
             Ok(v) => {
                 tokens.push((v, SimpleSpan::from(lexer.span())));
             }
@@ -182,6 +573,8 @@ impl std::fmt::Display for LexingError {
             LexingError::UnexpectedToken => write!(f, "Unexpected token"),
             LexingError::InvalidInteger(msg) => write!(f, "Invalid integer: {}", msg),
             LexingError::InvalidFloat(msg) => write!(f, "Invalid float: {}", msg),
+            LexingError::InvalidInterpolation(msg) => write!(f, "Invalid interpolation: {}", msg),
+            LexingError::InvalidString(msg) => write!(f, "Invalid string: {}", msg),
         }
     }
 }
\ No newline at end of file
@@ -1,8 +1,14 @@
 pub mod ast;
+pub mod checker;
+pub mod compiler;
+pub mod env;
 pub mod eval;
 pub mod lexer;
 pub mod parser;
+pub mod resolver;
 pub mod utils;
+pub mod value;
+pub mod vm;
 pub mod builtins;
 pub mod libraries;
 
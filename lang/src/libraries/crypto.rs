@@ -1,11 +1,12 @@
+use std::rc::Rc;
 use argon2::{PasswordHasher, PasswordVerifier};
 use sha2::Digest;
 
-use crate::{ast::{Expr, InternalFunctionResponse, Spanned, SpannedExpr}, lexer::Span};
+use crate::{ast::Spanned, lexer::Span, value::{InternalFunctionResponse, Value}};
 
-pub fn sha256(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn sha256(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let input = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => Err((
             "sha256 expects a string argument".to_string(),
             args[0].span.clone(),
@@ -16,14 +17,14 @@ pub fn sha256(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Str
     let hashed = format!("{:x}", hashed);
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(hashed),
+        return_value: Value::String(hashed.into()),
         replace_self: None,
     })
 }
 
-pub fn sha512(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn sha512(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let input = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => Err((
             "sha512 expects a string argument".to_string(),
             args[0].span.clone(),
@@ -34,14 +35,14 @@ pub fn sha512(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Str
     let hashed = format!("{:x}", hashed);
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(hashed),
+        return_value: Value::String(hashed.into()),
         replace_self: None,
     })
 }
 
-pub fn blake3(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn blake3(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let input = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => Err((
             "blake3 expects a string argument".to_string(),
             args[0].span.clone(),
@@ -51,14 +52,14 @@ pub fn blake3(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Str
     let hashed = blake3::hash(input.as_bytes());
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(hashed.to_hex().to_string()),
+        return_value: Value::String(hashed.to_hex().to_string().into()),
         replace_self: None,
     })
 }
 
-fn bcrypt_hash(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+fn bcrypt_hash(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let input = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => Err((
             "bcrypt_hash expects a string argument".to_string(),
             args[0].span.clone(),
@@ -72,14 +73,14 @@ fn bcrypt_hash(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (St
         ))?;
     
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(hashed),
+        return_value: Value::String(hashed.into()),
         replace_self: None,
     })
 }
 
-pub fn bcrypt_verify(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn bcrypt_verify(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let password = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => Err((
             "bcrypt_verify expects the first argument to be a string".to_string(),
             args[0].span.clone(),
@@ -87,28 +88,28 @@ pub fn bcrypt_verify(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionRespons
     };
 
     let hash = match &args[1].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => Err((
             "bcrypt_verify expects the second argument to be a string".to_string(),
             args[1].span.clone(),
         ))?,
     };
 
-    let is_valid = bcrypt::verify(password, hash)
+    let is_valid = bcrypt::verify(password, &hash)
         .map_err(|e| (
             format!("bcrypt_verify failed: {}", e),
             args[0].span.clone(),
         ))?;
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::Bool(is_valid),
+        return_value: Value::Bool(is_valid),
         replace_self: None,
     })
 }
 
-pub fn argon2_hash(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn argon2_hash(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let input = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => Err((
             "argon2_hash expects a string argument".to_string(),
             args[0].span.clone(),
@@ -128,14 +129,14 @@ pub fn argon2_hash(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse,
         .to_string();
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(hashed),
+        return_value: Value::String(hashed.into()),
         replace_self: None,
     })
 }
 
-pub fn argon2_verify(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn argon2_verify(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let password = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => Err((
             "argon2_verify expects the first argument to be a string".to_string(),
             args[0].span.clone(),
@@ -143,14 +144,14 @@ pub fn argon2_verify(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionRespons
     };
 
     let hash = match &args[1].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => Err((
             "argon2_verify expects the second argument to be a string".to_string(),
             args[1].span.clone(),
         ))?,
     };
 
-    let parsed_hash = argon2::PasswordHash::new(hash)
+    let parsed_hash = argon2::PasswordHash::new(&hash)
         .map_err(|e| (
             format!("argon2_verify failed to parse hash: {}", e),
             args[1].span.clone(),
@@ -161,14 +162,14 @@ pub fn argon2_verify(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionRespons
         .is_ok();
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::Bool(is_valid),
+        return_value: Value::Bool(is_valid),
         replace_self: None,
     })
 }
 
-pub fn scrypt_hash(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn scrypt_hash(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let input = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => Err((
             "scrypt_hash expects a string argument".to_string(),
             args[0].span.clone(),
@@ -186,14 +187,14 @@ pub fn scrypt_hash(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse,
     ))?;
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(hashed.to_string()),
+        return_value: Value::String(hashed.to_string().into()),
         replace_self: None,
     })
 }
 
-pub fn scrypt_verify(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn scrypt_verify(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let password = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => Err((
             "scrypt_verify expects the first argument to be a string".to_string(),
             args[0].span.clone(),
@@ -201,14 +202,14 @@ pub fn scrypt_verify(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionRespons
     };
 
     let hash = match &args[1].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => Err((
             "scrypt_verify expects the second argument to be a string".to_string(),
             args[1].span.clone(),
         ))?,
     };
 
-    let parsed_hash = scrypt::password_hash::PasswordHash::new(hash)
+    let parsed_hash = scrypt::password_hash::PasswordHash::new(&hash)
         .map_err(|e| (
             format!("scrypt_verify failed to parse hash: {}", e),
             args[1].span.clone(),
@@ -219,15 +220,15 @@ pub fn scrypt_verify(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionRespons
         .is_ok();
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::Bool(is_valid),
+        return_value: Value::Bool(is_valid),
         replace_self: None,
     })
 }
 
 // LEGACY
-pub fn md5(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn md5(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let input = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => Err((
             "md5 expects a string argument".to_string(),
             args[0].span.clone(),
@@ -238,147 +239,72 @@ pub fn md5(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     let hashed = format!("{:x}", hashed);
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(hashed),
+        return_value: Value::String(hashed.into()),
         replace_self: None,
     })
 }
 
-pub fn get_object() -> Expr {
+pub fn get_object() -> Value {
     let mut symbols = std::collections::HashMap::new();
 
     symbols.insert(
         "sha256".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "sha256".to_string(),
-                args: vec!["input".to_string()],
-                func: sha256,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("sha256", &["input"], sha256),
     );
 
     symbols.insert(
         "sha512".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "sha512".to_string(),
-                args: vec!["input".to_string()],
-                func: sha512,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("sha512", &["input"], sha512),
     );
 
     symbols.insert(
         "blake3".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "blake3".to_string(),
-                args: vec!["input".to_string()],
-                func: blake3,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("blake3", &["input"], blake3),
     );
 
     symbols.insert(
         "bcrypt_hash".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "bcrypt_hash".to_string(),
-                args: vec!["input".to_string()],
-                func: bcrypt_hash,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("bcrypt_hash", &["input"], bcrypt_hash),
     );
 
     symbols.insert(
         "bcrypt_verify".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "bcrypt_verify".to_string(),
-                args: vec!["input".to_string(), "hash".to_string()],
-                func: bcrypt_verify,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("bcrypt_verify", &["input", "hash"], bcrypt_verify),
     );
 
     symbols.insert(
         "argon2_hash".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "argon2_hash".to_string(),
-                args: vec!["input".to_string()],
-                func: argon2_hash,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("argon2_hash", &["input"], argon2_hash),
     );
 
     symbols.insert(
         "argon2_verify".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "argon2_verify".to_string(),
-                args: vec!["input".to_string(), "hash".to_string()],
-                func: argon2_verify,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("argon2_verify", &["input", "hash"], argon2_verify),
     );
 
     symbols.insert(
         "scrypt_hash".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "scrypt_hash".to_string(),
-                args: vec!["input".to_string()],
-                func: scrypt_hash,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("scrypt_hash", &["input"], scrypt_hash),
     );
 
     symbols.insert(
         "scrypt_verify".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "scrypt_verify".to_string(),
-                args: vec!["input".to_string(), "hash".to_string()],
-                func: scrypt_verify,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("scrypt_verify", &["input", "hash"], scrypt_verify),
     );
 
     symbols.insert(
         "legacy".to_string(),
-        SpannedExpr {
-            node: Expr::Module {
-                symbols: {
-                    let mut legacy_symbols = std::collections::HashMap::new();
-
-                    legacy_symbols.insert(
-                        "md5".to_string(),
-                        SpannedExpr {
-                            node: Expr::InternalFunction {
-                                name: "md5".to_string(),
-                                args: vec!["input".to_string()],
-                                func: md5,
-                            },
-                            span: Span::default(),
-                        },
-                    );
-
-                    legacy_symbols
-                },
-            },
-            span: Span::default(),
-        },
+        Value::Module(Rc::new({
+            let mut legacy_symbols = std::collections::HashMap::new();
+
+            legacy_symbols.insert(
+                "md5".to_string(),
+                Value::internal_function("md5", &["input"], md5),
+            );
+
+            legacy_symbols
+        })),
     );
 
-    Expr::Module { symbols }
+    Value::Module(Rc::new(symbols))
 }
\ No newline at end of file
@@ -1,10 +1,11 @@
+use std::rc::Rc;
 use std::collections::HashMap;
 
-use crate::{ast::{Expr, InternalFunctionResponse, Spanned, SpannedExpr}, lexer::Span};
+use crate::{ast::Spanned, lexer::Span, value::{InternalFunctionResponse, Value}};
 
-pub fn encode_base64(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn encode_base64(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let input = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => return Err((
             "encode_base64 expects a string argument".to_string(),
             args[0].span,
@@ -14,14 +15,14 @@ pub fn encode_base64(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionRespons
     let encoded = base64::encode(input);
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(encoded),
+        return_value: Value::String(encoded.into()),
         replace_self: None,
     })
 }
 
-pub fn decode_base64(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn decode_base64(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let input = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => return Err((
             "decode_base64 expects a string argument".to_string(),
             args[0].span,
@@ -39,14 +40,14 @@ pub fn decode_base64(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionRespons
     ))?;
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(decoded),
+        return_value: Value::String(decoded.into()),
         replace_self: None,
     })
 }
 
-pub fn encode_base16(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn encode_base16(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let input = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => return Err((
             "encode_base16 expects a string argument".to_string(),
             args[0].span,
@@ -56,21 +57,21 @@ pub fn encode_base16(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionRespons
     let encoded = base16::encode_lower(input.as_bytes());
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(encoded),
+        return_value: Value::String(encoded.into()),
         replace_self: None,
     })
 }
 
-pub fn decode_base16(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn decode_base16(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let input = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => return Err((
             "decode_base16 expects a string argument".to_string(),
             args[0].span,
         )),
     };
 
-    let decoded_bytes = base16::decode(input).map_err(|e| (
+    let decoded_bytes = base16::decode(&input).map_err(|e| (
         format!("Failed to decode base16 string: {}", e),
         args[0].span,
     ))?;
@@ -81,65 +82,33 @@ pub fn decode_base16(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionRespons
     ))?;
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(decoded),
+        return_value: Value::String(decoded.into()),
         replace_self: None,
     })
 }
 
-pub fn get_object() -> Expr {
+pub fn get_object() -> Value {
     let mut symbols = HashMap::new();
 
     symbols.insert(
         "encode_base64".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "encode_base64".to_string(),
-                args: vec!["str".to_string()],
-                func: encode_base64,
-            },
-
-            span: Span::default(),
-        },
+        Value::internal_function("encode_base64", &["str"], encode_base64),
     );
 
     symbols.insert(
         "decode_base64".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "decode_base64".to_string(),
-                args: vec!["str".to_string()],
-                func: decode_base64,
-            },
-
-            span: Span::default(),
-        },
+        Value::internal_function("decode_base64", &["str"], decode_base64),
     );
 
     symbols.insert(
         "encode_base16".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "encode_base16".to_string(),
-                args: vec!["str".to_string()],
-                func: encode_base16,
-            },
-
-            span: Span::default(),
-        },
+        Value::internal_function("encode_base16", &["str"], encode_base16),
     );
 
     symbols.insert(
         "decode_base16".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "decode_base16".to_string(),
-                args: vec!["str".to_string()],
-                func: decode_base16,
-            },
-
-            span: Span::default(),
-        },
+        Value::internal_function("decode_base16", &["str"], decode_base16),
     );
 
-    Expr::Module { symbols }
+    Value::Module(Rc::new(symbols))
 }
\ No newline at end of file
@@ -1,28 +1,29 @@
+use std::rc::Rc;
 use std::path::PathBuf;
-use crate::{ast::{Expr, InternalFunctionResponse, Spanned, SpannedExpr}, lexer::Span};
+use crate::{ast::Spanned, lexer::Span, value::{InternalFunctionResponse, Value}};
 
 type FFIFunction = unsafe extern "C" fn(i32, *const modu_ffi::FFIValue) -> modu_ffi::FFIValue;
 
 pub fn execute_ffi_call(
     lib: &std::sync::Arc<libloading::Library>,
     func_name: &str,
-    args: Vec<Spanned<Expr>>,
-) -> Result<Expr, String> {
+    args: Vec<Spanned<Value>>,
+) -> Result<Value, String> {
     let mut ffi_args = Vec::new();
     let mut owned_strings = Vec::<*mut std::ffi::c_char>::new();
 
     for arg in args {
         match arg.node {
-            Expr::Int(i) => {
+            Value::Int(i) => {
                 ffi_args.push(modu_ffi::FFIValue::integer(i));
             }
 
-            Expr::Float(f) => {
+            Value::Float(f) => {
                 ffi_args.push(modu_ffi::FFIValue::float(f));
             }
 
-            Expr::String(s) => {
-                let c_string = std::ffi::CString::new(s.clone())
+            Value::String(s) => {
+                let c_string = std::ffi::CString::new(s.as_bytes())
                     .map_err(|e| format!("Failed to convert string to C string: {}", e))?;
                 let ptr = c_string.into_raw();
                 owned_strings.push(ptr);
@@ -30,11 +31,11 @@ pub fn execute_ffi_call(
                 ffi_args.push(modu_ffi::FFIValue::string(ptr));
             }
 
-            Expr::Bool(b) => {
+            Value::Bool(b) => {
                 ffi_args.push(modu_ffi::FFIValue::boolean(b));
             }
 
-            Expr::Null => {
+            Value::Null => {
                 ffi_args.push(modu_ffi::FFIValue::null());
             }
 
@@ -55,8 +56,8 @@ pub fn execute_ffi_call(
         }
 
         match result.ty {
-            modu_ffi::FFIType::Integer => Ok(Expr::Int(result.value.integer as i64)),
-            modu_ffi::FFIType::Float => Ok(Expr::Float(result.value.float as f64)),
+            modu_ffi::FFIType::Integer => Ok(Value::Int(result.value.integer as i64)),
+            modu_ffi::FFIType::Float => Ok(Value::Float(result.value.float as f64)),
             modu_ffi::FFIType::String => {
                 let c_str = std::ffi::CStr::from_ptr(result.value.string);
                 let str_slice = c_str.to_str()
@@ -65,17 +66,17 @@ pub fn execute_ffi_call(
                 let string = str_slice.to_string();
                 modu_ffi::ffi_free_string(result.value.string);
                 
-                Ok(Expr::String(string))
+                Ok(Value::String(string.into()))
             }
-            modu_ffi::FFIType::Boolean => Ok(Expr::Bool(result.value.boolean)),
-            modu_ffi::FFIType::Null => Ok(Expr::Null),
+            modu_ffi::FFIType::Boolean => Ok(Value::Bool(result.value.boolean)),
+            modu_ffi::FFIType::Null => Ok(Value::Null),
         }
     }
 }
 
-pub fn load(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn load(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let path = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => return Err((
             "load expects a string argument".to_string(),
             args[0].span,
@@ -102,33 +103,26 @@ pub fn load(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Strin
         ))?.to_string() + "/";
     }
 
-    full_path.push_str(path);
+    full_path.push_str(&path);
 
     unsafe {
         let library = libloading::Library::new(&full_path)
             .map_err(|e| (format!("Failed to load FFI library: {}", e), args[0].span.clone()))?;
 
         Ok(InternalFunctionResponse {
-            return_value: Expr::FFILibrary(std::sync::Arc::new(library)),
+            return_value: Value::FFILibrary(std::sync::Arc::new(library)),
             replace_self: None,
         })
     }
 }
 
-pub fn get_object() -> Expr {
+pub fn get_object() -> Value {
     let mut symbols = std::collections::HashMap::new();
 
     symbols.insert(
         "load".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "load".to_string(),
-                args: vec!["path".to_string()],
-                func: load,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("load", &["path"], load),
     );
 
-    Expr::Module { symbols }
+    Value::Module(Rc::new(symbols))
 }
\ No newline at end of file
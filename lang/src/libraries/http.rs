@@ -1,6 +1,7 @@
+use std::rc::Rc;
 use std::collections::HashMap;
 
-use crate::{ast::{Expr, InternalFunctionResponse, Spanned, SpannedExpr}, lexer::Span};
+use crate::{ast::Spanned, lexer::Span, value::{InternalFunctionResponse, Value}};
 
 fn handle_response(response: reqwest::blocking::Response) -> Result<InternalFunctionResponse, (String, Span)> {
     let status = response.status();
@@ -9,22 +10,22 @@ fn handle_response(response: reqwest::blocking::Response) -> Result<InternalFunc
 
     properties.insert(
         "status".to_string(),
-        Expr::Int(status.as_u16() as i64)
+        Value::Int(status.as_u16() as i64)
     );
 
     properties.insert(
         "status_text".to_string(),
-        Expr::String(status.canonical_reason().unwrap_or("").to_string())
+        Value::String(status.canonical_reason().unwrap_or("").into())
     );
 
-    let headers = Expr::Object {
-        properties: response.headers().iter().map(|(k, v)| {
+    let headers = Value::Object(Rc::new(
+        response.headers().iter().map(|(k, v)| {
             (
                 k.to_string(),
-                Expr::String(v.to_str().unwrap_or("").to_string())
+                Value::String(v.to_str().unwrap_or("").into())
             )
         }).collect(),
-    };
+    ));
 
     properties.insert(
         "headers".to_string(),
@@ -38,23 +39,23 @@ fn handle_response(response: reqwest::blocking::Response) -> Result<InternalFunc
 
     properties.insert(
         "body".to_string(),
-        Expr::String(body)
+        Value::String(body.into())
     );
 
     properties.insert(
         "ok".to_string(),
-        Expr::Bool(status.is_success())
+        Value::Bool(status.is_success())
     );
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::Object { properties },
+        return_value: Value::Object(Rc::new(properties)),
         replace_self: None,
     })
 }
 
-pub fn get(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn get(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let url = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => return Err((
             "get expects a string argument".to_string(),
             args[0].span,
@@ -69,9 +70,9 @@ pub fn get(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     handle_response(response)
 }
 
-pub fn post(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn post(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let url = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => return Err((
             "post expects a string as the first argument".to_string(),
             args[0].span,
@@ -79,7 +80,7 @@ pub fn post(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Strin
     };
 
     let body = match &args[1].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => return Err((
             "post expects a string as the second argument".to_string(),
             args[1].span,
@@ -98,9 +99,9 @@ pub fn post(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Strin
     handle_response(response)
 }
 
-pub fn put(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn put(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let url = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => return Err((
             "put expects a string as the first argument".to_string(),
             args[0].span,
@@ -108,7 +109,7 @@ pub fn put(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     };
 
     let body = match &args[1].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => return Err((
             "put expects a string as the second argument".to_string(),
             args[1].span,
@@ -127,9 +128,9 @@ pub fn put(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     handle_response(response)
 }
 
-pub fn patch(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn patch(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let url = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => return Err((
             "patch expects a string as the first argument".to_string(),
             args[0].span,
@@ -137,7 +138,7 @@ pub fn patch(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Stri
     };
 
     let body = match &args[1].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => return Err((
             "patch expects a string as the second argument".to_string(),
             args[1].span,
@@ -156,9 +157,9 @@ pub fn patch(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Stri
     handle_response(response)
 }
 
-pub fn delete(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn delete(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let url = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => return Err((
             "delete expects a string as the first argument".to_string(),
             args[0].span,
@@ -176,68 +177,33 @@ pub fn delete(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Str
     handle_response(response)
 }
 
-pub fn get_object() -> Expr {
+pub fn get_object() -> Value {
     let mut symbols = std::collections::HashMap::new();
 
     symbols.insert(
         "get".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "get".to_string(),
-                args: vec!["url".to_string()],
-                func: get,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("get", &["url"], get),
     );
 
     symbols.insert(
         "post".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "post".to_string(),
-                args: vec!["url".to_string(), "body".to_string()],
-                func: post,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("post", &["url", "body"], post),
     );
 
     symbols.insert(
         "put".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "put".to_string(),
-                args: vec!["url".to_string(), "body".to_string()],
-                func: put,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("put", &["url", "body"], put),
     );
 
     symbols.insert(
         "patch".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "patch".to_string(),
-                args: vec!["url".to_string(), "body".to_string()],
-                func: patch,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("patch", &["url", "body"], patch),
     );
 
     symbols.insert(
         "delete".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "delete".to_string(),
-                args: vec!["url".to_string()],
-                func: delete,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("delete", &["url"], delete),
     );
 
-    Expr::Module { symbols }
+    Value::Module(Rc::new(symbols))
 }
\ No newline at end of file
@@ -1,48 +1,49 @@
+use std::rc::Rc;
 use std::collections::HashMap;
 
-use crate::{ast::{Expr, InternalFunctionResponse, Spanned, SpannedExpr}, lexer::Span};
+use crate::{ast::Spanned, lexer::Span, value::{InternalFunctionResponse, Value}};
 
-pub fn new(_: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn new(_: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     Ok(InternalFunctionResponse {
-        return_value: Expr::Object { properties: std::collections::HashMap::new() },
+        return_value: Value::Object(Rc::new(std::collections::HashMap::new())),
         replace_self: None,
     })
 }
 
-pub fn parse_obj(obj: &mut HashMap<String, serde_json::Value>) -> HashMap<String, Expr> {
+pub fn parse_obj(obj: &mut HashMap<String, serde_json::Value>) -> HashMap<String, Value> {
     let mut map = HashMap::new();
 
     for (key, value) in obj.drain() {
         match value {
             serde_json::Value::Null => {
-                map.insert(key, Expr::Null);
+                map.insert(key, Value::Null);
             }
             
             serde_json::Value::Bool(b) => {
-                map.insert(key, Expr::Bool(b));
+                map.insert(key, Value::Bool(b));
             }
 
             serde_json::Value::Number(n) => {
                 if let Some(i) = n.as_i64() {
-                    map.insert(key, Expr::Int(i));
+                    map.insert(key, Value::Int(i));
                 } else if let Some(f) = n.as_f64() {
-                    map.insert(key, Expr::Float(f));
+                    map.insert(key, Value::Float(f));
                 }
             }
 
             serde_json::Value::String(s) => {
-                map.insert(key, Expr::String(s));
+                map.insert(key, Value::String(s.into()));
             }
 
             serde_json::Value::Object(o) => {
                 let mut hashmap: HashMap<String, serde_json::Value> = o.into_iter().collect();
 
                 let properties = parse_obj(&mut hashmap);
-                map.insert(key, Expr::Object { properties });
+                map.insert(key, Value::Object(Rc::new(properties)));
             }
 
             v => {
-                map.insert(key, Expr::String(v.to_string()));
+                map.insert(key, Value::String(v.to_string().into()));
             }
         }
     }
@@ -50,9 +51,9 @@ pub fn parse_obj(obj: &mut HashMap<String, serde_json::Value>) -> HashMap<String
     map
 }
 
-pub fn parse(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn parse(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let json_str = match &args[0].node {
-        Expr::String(s) => s,
+        Value::String(s) => s.to_string(),
         _ => {
             return Err((
                 "parse expects a string as the first argument".to_string(),
@@ -61,7 +62,7 @@ pub fn parse(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Stri
         }
     };
 
-    let mut parsed: HashMap<String, serde_json::Value> = serde_json::from_str(json_str).map_err(|e| (
+    let mut parsed: HashMap<String, serde_json::Value> = serde_json::from_str(&json_str).map_err(|e| (
         format!("Failed to parse JSON: {}", e),
         args[0].span,
     ))?;
@@ -69,37 +70,23 @@ pub fn parse(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Stri
     let properties = parse_obj(&mut parsed);
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::Object { properties },
+        return_value: Value::Object(Rc::new(properties)),
         replace_self: None,
     })
 }
 
-pub fn get_object() -> Expr {
+pub fn get_object() -> Value {
     let mut symbols = std::collections::HashMap::new();
 
     symbols.insert(
         "new".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "new".to_string(),
-                args: vec![],
-                func: new,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("new", &[], new),
     );
 
     symbols.insert(
         "parse".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "parse".to_string(),
-                args: vec!["json_str".to_string()],
-                func: parse,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("parse", &["json_str"], parse),
     );
 
-    Expr::Module { symbols }
+    Value::Module(Rc::new(symbols))
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
-use crate::{ast::{Expr, InternalFunctionResponse, Spanned, SpannedExpr}, lexer::Span};
+use std::rc::Rc;
+use crate::{ast::Spanned, lexer::Span, value::{InternalFunctionResponse, Value}};
 
-pub fn mul(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn mul(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     if args.len() != 2 {
         return Err((
             "mul takes exactly two arguments".to_string(),
@@ -9,34 +10,34 @@ pub fn mul(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     }
 
     match (&args[0].node, &args[1].node) {
-        (Expr::Int(a), Expr::Int(b)) => {
+        (Value::Int(a), Value::Int(b)) => {
             let result = a * b;
             Ok(InternalFunctionResponse {
-                return_value: Expr::Int(result),
+                return_value: Value::Int(result),
                 replace_self: None,
             })
         }
 
-        (Expr::Float(a), Expr::Float(b)) => {
+        (Value::Float(a), Value::Float(b)) => {
             let result = a * b;
             Ok(InternalFunctionResponse {
-                return_value: Expr::Float(result),
+                return_value: Value::Float(result),
                 replace_self: None,
             })
         }
 
-        (Expr::Int(a), Expr::Float(b)) => {
+        (Value::Int(a), Value::Float(b)) => {
             let result = (*a as f64) * b;
             Ok(InternalFunctionResponse {
-                return_value: Expr::Float(result),
+                return_value: Value::Float(result),
                 replace_self: None,
             })
         }
 
-        (Expr::Float(a), Expr::Int(b)) => {
+        (Value::Float(a), Value::Int(b)) => {
             let result = a * (*b as f64);
             Ok(InternalFunctionResponse {
-                return_value: Expr::Float(result),
+                return_value: Value::Float(result),
                 replace_self: None,
             })
         }
@@ -48,7 +49,7 @@ pub fn mul(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     }
 }
 
-pub fn div(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn div(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     if args.len() != 2 {
         return Err((
             "div takes exactly two arguments".to_string(),
@@ -57,7 +58,7 @@ pub fn div(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     }
 
     match (&args[0].node, &args[1].node) {
-        (Expr::Int(a), Expr::Int(b)) => {
+        (Value::Int(a), Value::Int(b)) => {
             if *b == 0 {
                 return Err((
                     "division by zero".to_string(),
@@ -66,12 +67,12 @@ pub fn div(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
             }
             let result = (*a as f64) / (*b as f64);
             Ok(InternalFunctionResponse {
-                return_value: Expr::Float(result),
+                return_value: Value::Float(result),
                 replace_self: None,
             })
         }
 
-        (Expr::Float(a), Expr::Float(b)) => {
+        (Value::Float(a), Value::Float(b)) => {
             if *b == 0.0 {
                 return Err((
                     "division by zero".to_string(),
@@ -80,12 +81,12 @@ pub fn div(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
             }
             let result = a / b;
             Ok(InternalFunctionResponse {
-                return_value: Expr::Float(result),
+                return_value: Value::Float(result),
                 replace_self: None,
             })
         }
 
-        (Expr::Int(a), Expr::Float(b)) => {
+        (Value::Int(a), Value::Float(b)) => {
             if *b == 0.0 {
                 return Err((
                     "division by zero".to_string(),
@@ -94,12 +95,12 @@ pub fn div(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
             }
             let result = (*a as f64) / b;
             Ok(InternalFunctionResponse {
-                return_value: Expr::Float(result),
+                return_value: Value::Float(result),
                 replace_self: None,
             })
         }
 
-        (Expr::Float(a), Expr::Int(b)) => {
+        (Value::Float(a), Value::Int(b)) => {
             if *b == 0 {
                 return Err((
                     "division by zero".to_string(),
@@ -108,7 +109,7 @@ pub fn div(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
             }
             let result = a / (*b as f64);
             Ok(InternalFunctionResponse {
-                return_value: Expr::Float(result),
+                return_value: Value::Float(result),
                 replace_self: None,
             })
         }
@@ -120,7 +121,7 @@ pub fn div(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     }
 }
 
-pub fn abs(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn abs(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     if args.len() != 1 {
         return Err((
             "abs takes exactly one argument".to_string(),
@@ -129,18 +130,18 @@ pub fn abs(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     }
 
     match &args[0].node {
-        Expr::Int(n) => {
+        Value::Int(n) => {
             let abs_value = n.abs();
             Ok(InternalFunctionResponse {
-                return_value: Expr::Int(abs_value),
+                return_value: Value::Int(abs_value),
                 replace_self: None,
             })
         }
 
-        Expr::Float(f) => {
+        Value::Float(f) => {
             let abs_value = f.abs();
             Ok(InternalFunctionResponse {
-                return_value: Expr::Float(abs_value),
+                return_value: Value::Float(abs_value),
                 replace_self: None,
             })
         }
@@ -152,7 +153,7 @@ pub fn abs(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     }
 }
 
-pub fn pow(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn pow(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     if args.len() != 2 {
         return Err((
             "pow takes exactly two arguments".to_string(),
@@ -161,34 +162,34 @@ pub fn pow(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     }
 
     match (&args[0].node, &args[1].node) {
-        (Expr::Int(a), Expr::Int(b)) => {
+        (Value::Int(a), Value::Int(b)) => {
             let result = a.pow(*b as u32);
             Ok(InternalFunctionResponse {
-                return_value: Expr::Int(result),
+                return_value: Value::Int(result),
                 replace_self: None,
             })
         }
 
-        (Expr::Float(a), Expr::Float(b)) => {
+        (Value::Float(a), Value::Float(b)) => {
             let result = a.powf(*b);
             Ok(InternalFunctionResponse {
-                return_value: Expr::Float(result),
+                return_value: Value::Float(result),
                 replace_self: None,
             })
         }
 
-        (Expr::Int(a), Expr::Float(b)) => {
+        (Value::Int(a), Value::Float(b)) => {
             let result = (*a as f64).powf(*b);
             Ok(InternalFunctionResponse {
-                return_value: Expr::Float(result),
+                return_value: Value::Float(result),
                 replace_self: None,
             })
         }
 
-        (Expr::Float(a), Expr::Int(b)) => {
+        (Value::Float(a), Value::Int(b)) => {
             let result = a.powf(*b as f64);
             Ok(InternalFunctionResponse {
-                return_value: Expr::Float(result),
+                return_value: Value::Float(result),
                 replace_self: None,
             })
         }
@@ -200,56 +201,28 @@ pub fn pow(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String
     }
 }
 
-pub fn get_object() -> Expr {
+pub fn get_object() -> Value {
     let mut symbols = std::collections::HashMap::new();
 
     symbols.insert(
         "mul".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "mul".to_string(),
-                args: vec!["a".to_string(), "b".to_string()],
-                func: mul,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("mul", &["a", "b"], mul),
     );
 
     symbols.insert(
         "div".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "div".to_string(),
-                args: vec!["a".to_string(), "b".to_string()],
-                func: div,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("div", &["a", "b"], div),
     );
 
     symbols.insert(
         "abs".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "abs".to_string(),
-                args: vec!["x".to_string()],
-                func: abs,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("abs", &["x"], abs),
     );
 
     symbols.insert(
         "pow".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "pow".to_string(),
-                args: vec!["base".to_string(), "exponent".to_string()],
-                func: pow,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("pow", &["base", "exponent"], pow),
     );
 
-    Expr::Module { symbols }
+    Value::Module(Rc::new(symbols))
 }
\ No newline at end of file
@@ -11,7 +11,7 @@ mod crypto;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod ffi;
 
-pub fn get_package(name: &str) -> Option<crate::ast::Expr> {
+pub fn get_package(name: &str) -> Option<crate::value::Value> {
     match name {
         "time" => Some(time::get_object()),
         "encoding" => Some(encoding::get_object()),
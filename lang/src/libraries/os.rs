@@ -1,5 +1,6 @@
+use std::rc::Rc;
 use std::process::Command;
-use crate::{ast::{Expr, InternalFunctionResponse, Spanned, SpannedExpr}, lexer::Span};
+use crate::{ast::Spanned, value::{InternalFunctionResponse, Value, ValueIterator}};
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
@@ -13,7 +14,7 @@ fn clean_command(cmd: String) -> String {
 	return clean;
 }
 
-pub fn exec(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, crate::lexer::Span)> {
+pub fn exec(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, crate::lexer::Span)> {
     if args.len() != 1 {
         return Err((
             "exec takes exactly one argument".to_string(),
@@ -22,7 +23,7 @@ pub fn exec(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Strin
     }
 
     let command_str = match &args[0].node {
-        Expr::String(s) => clean_command(s.clone()),
+        Value::String(s) => clean_command(s.to_string()),
         _ => return Err((
             "exec expects a string argument".to_string(),
             args[0].span,
@@ -64,33 +65,55 @@ pub fn exec(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (Strin
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(stdout),
+        return_value: Value::String(stdout.into()),
         replace_self: None,
     })
 }
 
-pub fn get_object() -> Expr {
+pub fn read_lines(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, crate::lexer::Span)> {
+    let path = match &args[0].node {
+        Value::String(s) => s.to_string(),
+        _ => return Err((
+            "read_lines expects a string argument".to_string(),
+            args[0].span,
+        )),
+    };
+
+    let file = std::fs::File::open(&path).map_err(|e| (
+        format!("Failed to open file {}: {}", path, e),
+        args[0].span,
+    ))?;
+
+    // lines are read one at a time while the loop runs, not all at once
+    let lines = std::io::BufRead::lines(std::io::BufReader::new(file))
+        .map(|line| line
+            .map(|line| Value::String(line.into()))
+            .map_err(|e| format!("Failed to read line: {}", e))
+        );
+
+    Ok(InternalFunctionResponse {
+        return_value: Value::Iterator(ValueIterator::new(lines)),
+        replace_self: None,
+    })
+}
+
+pub fn get_object() -> Value {
     let mut symbols = std::collections::HashMap::new();
 
     symbols.insert(
         "exec".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "exec".to_string(),
-                args: vec!["cmd".to_string()],
-                func: exec,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("exec", &["cmd"], exec),
+    );
+
+    symbols.insert(
+        "read_lines".to_string(),
+        Value::internal_function("read_lines", &["path"], read_lines),
     );
 
     symbols.insert(
         "name".to_string(),
-        SpannedExpr {
-            node: Expr::String(std::env::consts::OS.to_string()),
-            span: Span::default(),
-        },
+        Value::String(std::env::consts::OS.into()),
     );
 
-    Expr::Module { symbols }
+    Value::Module(Rc::new(symbols))
 }
\ No newline at end of file
@@ -1,43 +1,44 @@
+use std::rc::Rc;
 use std::{collections::HashMap, time};
 use chrono::{DateTime, Local};
 
-use crate::{ast::{Expr, InternalFunctionResponse, Spanned, SpannedExpr}, lexer::Span};
+use crate::{ast::Spanned, lexer::Span, value::{InternalFunctionResponse, Value}};
 
-pub fn now_unix(_: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn now_unix(_: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .map_err(|e| (format!("System time error: {}", e), Span::default()))?
         .as_secs() as i64;
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::Int(now),
+        return_value: Value::Int(now),
         replace_self: None,
     })
 }
 
-pub fn now_utc(_: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn now_utc(_: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let now = time::SystemTime::now();
     let datetime: DateTime<chrono::Utc> = now.into();
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(format!("{}", datetime.format("%c"))),
+        return_value: Value::String(format!("{}", datetime.format("%c")).into()),
         replace_self: None,
     })
 }
 
-pub fn now_local(_: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn now_local(_: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let now = time::SystemTime::now();
     let datetime: DateTime<chrono::Local> = now.into();
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(format!("{}", datetime.format("%c"))),
+        return_value: Value::String(format!("{}", datetime.format("%c")).into()),
         replace_self: None,
     })
 }
 
-pub fn to_iso_8601(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn to_iso_8601(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let time = match args[0].node {
-        Expr::Int(n) => n,
+        Value::Int(n) => n,
         _ => return Err(("to_iso_8601 expects an integer unix timestamp".to_string(), args[0].span)),
     };
 
@@ -45,14 +46,14 @@ pub fn to_iso_8601(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse,
     let time: DateTime<Local> = time.into();
     
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(time.to_rfc3339()),
+        return_value: Value::String(time.to_rfc3339().into()),
         replace_self: None,
     })
 }
 
-pub fn to_rfc_2822(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn to_rfc_2822(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let time = match args[0].node {
-        Expr::Int(n) => n,
+        Value::Int(n) => n,
         _ => return Err(("to_rfc_2822 expects an integer unix timestamp".to_string(), args[0].span)),
     };
 
@@ -60,14 +61,14 @@ pub fn to_rfc_2822(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse,
     let time: DateTime<Local> = time.into();
     
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(time.to_rfc2822()),
+        return_value: Value::String(time.to_rfc2822().into()),
         replace_self: None,
     })
 }
 
-pub fn to_local_date_time(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn to_local_date_time(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let time = match args[0].node {
-        Expr::Int(n) => n,
+        Value::Int(n) => n,
         _ => return Err(("to_local_date_time expects an integer unix timestamp".to_string(), args[0].span)),
     };
 
@@ -75,14 +76,14 @@ pub fn to_local_date_time(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionRe
     let time: DateTime<Local> = time.into();
     
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(format!("{}", time.format("%c"))),
+        return_value: Value::String(format!("{}", time.format("%c")).into()),
         replace_self: None,
     })
 }
 
-pub fn to_utc_date_time(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn to_utc_date_time(args: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let time = match args[0].node {
-        Expr::Int(n) => n,
+        Value::Int(n) => n,
         _ => return Err(("to_utc_date_time expects an integer unix timestamp".to_string(), args[0].span)),
     };
 
@@ -90,104 +91,48 @@ pub fn to_utc_date_time(args: Vec<Spanned<Expr>>) -> Result<InternalFunctionResp
     let time: DateTime<chrono::Utc> = time.into();
     
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(format!("{}", time.format("%c"))),
+        return_value: Value::String(format!("{}", time.format("%c")).into()),
         replace_self: None,
     })
 }
 
-pub fn get_object() -> Expr {
+pub fn get_object() -> Value {
     let mut symbols = HashMap::new();
 
     symbols.insert(
         "now_unix".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "now_unix".to_string(),
-                args: vec![],
-                func: now_unix,
-            },
-
-            span: Span::default(),
-        },
+        Value::internal_function("now_unix", &[], now_unix),
     );
 
     symbols.insert(
         "now_utc".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "now_utc".to_string(),
-                args: vec![],
-                func: now_utc,
-            },
-
-            span: Span::default(),
-        },
+        Value::internal_function("now_utc", &[], now_utc),
     );
 
     symbols.insert(
         "now_local".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "now_local".to_string(),
-                args: vec![],
-                func: now_local,
-            },
-
-            span: Span::default(),
-        },
+        Value::internal_function("now_local", &[], now_local),
     );
 
     symbols.insert(
         "to_iso_8601".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "to_iso_8601".to_string(),
-                args: vec!["unix_timestamp".to_string()],
-                func: to_iso_8601,
-            },
-
-            span: Span::default(),
-        },
+        Value::internal_function("to_iso_8601", &["unix_timestamp"], to_iso_8601),
     );
 
     symbols.insert(
         "to_rfc_2822".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "to_rfc_2822".to_string(),
-                args: vec!["unix_timestamp".to_string()],
-                func: to_rfc_2822,
-            },
-
-            span: Span::default(),
-        },
+        Value::internal_function("to_rfc_2822", &["unix_timestamp"], to_rfc_2822),
     );
 
     symbols.insert(
         "to_local_date_time".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "to_local_date_time".to_string(),
-                args: vec!["unix_timestamp".to_string()],
-                func: to_local_date_time,
-            },
-
-            span: Span::default(),
-        },
+        Value::internal_function("to_local_date_time", &["unix_timestamp"], to_local_date_time),
     );
 
     symbols.insert(
         "to_utc_date_time".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "to_utc_date_time".to_string(),
-                args: vec!["unix_timestamp".to_string()],
-                func: to_utc_date_time,
-            },
-
-            span: Span::default(),
-        },
+        Value::internal_function("to_utc_date_time", &["unix_timestamp"], to_utc_date_time),
     );
 
-    Expr::Module { symbols }
+    Value::Module(Rc::new(symbols))
 }
\ No newline at end of file
@@ -1,49 +1,36 @@
-use crate::{ast::{Expr, InternalFunctionResponse, Spanned, SpannedExpr}, lexer::Span};
+use std::rc::Rc;
+use crate::{ast::Spanned, lexer::Span, value::{InternalFunctionResponse, Value}};
 
-pub fn v4(_: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn v4(_: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let uuid = uuid::Uuid::new_v4();
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(uuid.to_string()),
+        return_value: Value::String(uuid.to_string().into()),
         replace_self: None,
     })
 }
 
-pub fn v7(_: Vec<Spanned<Expr>>) -> Result<InternalFunctionResponse, (String, Span)> {
+pub fn v7(_: Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)> {
     let uuid = uuid::Uuid::now_v7();
 
     Ok(InternalFunctionResponse {
-        return_value: Expr::String(uuid.to_string()),
+        return_value: Value::String(uuid.to_string().into()),
         replace_self: None,
     })
 }
 
-pub fn get_object() -> Expr {
+pub fn get_object() -> Value {
     let mut symbols = std::collections::HashMap::new();
 
     symbols.insert(
         "v4".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "v4".to_string(),
-                args: vec![],
-                func: v4,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("v4", &[], v4),
     );
 
     symbols.insert(
         "v7".to_string(),
-        SpannedExpr {
-            node: Expr::InternalFunction {
-                name: "v7".to_string(),
-                args: vec![],
-                func: v7,
-            },
-            span: Span::default(),
-        },
+        Value::internal_function("v7", &[], v7),
     );
 
-    Expr::Module { symbols }
+    Value::Module(Rc::new(symbols))
 }
\ No newline at end of file
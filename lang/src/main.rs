@@ -4,11 +4,17 @@ use std::panic::{catch_unwind, AssertUnwindSafe};
 use colored::Colorize;
 
 mod ast;
+mod checker;
+mod compiler;
+mod env;
 mod eval;
 mod lexer;
 mod parser;
+mod resolver;
 mod cli;
 mod utils;
+mod value;
+mod vm;
 mod libraries;
 mod builtins;
 
@@ -20,6 +26,7 @@ fn main() {
     if args.len() < 2 {
         println!("Commands:
     run     <file> - Run a Modu file
+    check   <file> - Check a Modu file for type errors without running it
     repl           - Start the Modu REPL
     server  [port] - Start the Modu server, default port is 2424
     init           - Initialize a new Modu package
@@ -35,6 +42,7 @@ fn main() {
     let result = catch_unwind(AssertUnwindSafe(|| {
         match action.as_str() {
             "run" => cli::run::run(),
+            "check" => cli::check::check(),
             "repl" => cli::repl::repl(),
             "server" => cli::server::server(),
             "login" => cli::login::login(),
@@ -1,11 +1,14 @@
-use std::collections::HashMap;
 use ariadne::{Color, Label, Report, ReportKind, Source};
 use chumsky::prelude::*;
-use crate::{ast::{Expr, SpannedExpr}, eval, lexer::{Span, Token, lex}};
+use std::rc::Rc;
+use crate::{ast::{Arity, Expr, MatchArm, Method, Pattern, Signature, Spanned, SpannedExpr, Type}, checker, env::{self, Env, Globals}, eval::{self, BinaryOp}, lexer::{Span, Token, lex}, resolver, value::Value, vm};
+
+// the catch (with the name of the error) and finally blocks after a try block
+type TryClauses = (Option<(Option<String>, SpannedExpr)>, Option<SpannedExpr>);
 
 enum Postfix {
     Property(String, Span),
-    Call(Vec<SpannedExpr>),
+    Call(Vec<SpannedExpr>, Vec<(Spanned<String>, SpannedExpr)>),
     Index(SpannedExpr),
 }
 
@@ -22,13 +25,406 @@ fn report_error(report: Report<'_, (&str, std::ops::Range<usize>)>, source_name:
     }
 }
 
+// like report_error, but the error and the calls in its trace can be in different files
+fn report_eval_error(e: &eval::EvalError, source: Rc<env::Source>) {
+    // errors from functions defined in an imported module point into that module
+    let source = e.source.clone().unwrap_or(source);
+    let filename = source.name.clone();
+
+    let mut files = vec![(filename.clone(), source.text.clone())];
+
+    // deep recursion repeats the same call thousands of times, so calls from the same
+    // place are shown once with a count, both as a label and in the call stack below
+    let same_call = |a: &eval::TraceFrame, b: &eval::TraceFrame| a.span == b.span && Rc::ptr_eq(&a.source, &b.source);
+    let mut calls: Vec<(&eval::TraceFrame, usize)> = vec![];
+    let mut stack: Vec<(&eval::TraceFrame, usize)> = vec![];
+
+    for call in &e.trace {
+        if !files.iter().any(|(name, _)| *name == call.source.name) {
+            files.push((call.source.name.clone(), call.source.text.clone()));
+        }
+
+        match calls.iter_mut().find(|(seen, _)| same_call(seen, call)) {
+            Some((_, count)) => *count += 1,
+            None => calls.push((call, 1)),
+        }
+
+        match stack.last_mut() {
+            Some((last, count)) if same_call(last, call) => *count += 1,
+            _ => stack.push((call, 1)),
+        }
+    }
+
+    let times = |count: usize| if count > 1 { format!(" ({} times)", count) } else { String::new() };
+
+    let mut trace = String::from("call stack, innermost first:");
+
+    for (i, (call, count)) in stack.iter().enumerate() {
+        // only the ends of a very long stack are interesting
+        if stack.len() > 20 && i == 10 {
+            trace.push_str(&format!("\n    ... {} more", stack.len() - 20));
+        }
+
+        if stack.len() > 20 && i >= 10 && i < stack.len() - 10 {
+            continue;
+        }
+
+        let (line, column) = call.source.location(call.span.start);
+        trace.push_str(&format!("\n    {} called at {}:{}:{}{}", call.function, call.source.name, line, column, times(*count)));
+    }
+
+    let mut report = Report::build(ReportKind::Error, (filename.clone(), e.span.into_range()))
+        .with_code(1)
+        .with_message(format!("Evaluation error: {}", e.message))
+        .with_label(
+            Label::new((filename, e.span.into_range()))
+                .with_color(Color::Red)
+                .with_message(format!("{}", e.message_short)),
+        );
+
+    for (call, count) in &calls {
+        report.add_label(
+            Label::new((call.source.name.clone(), call.span.into_range()))
+                .with_color(Color::Yellow)
+                .with_message(format!("in {}, called here{}", call.function, times(*count))),
+        );
+    }
+
+    if !e.trace.is_empty() {
+        report.set_note(trace);
+    }
+
+    let report = report.finish();
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let mut writer = crate::WasmWriter;
+        let _ = report.write(ariadne::sources(files), &mut writer);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let _ = report.eprint(ariadne::sources(files));
+    }
+}
+
+// the value a function body ends with is what it returns, a call there is turned into
+// return f(x); so it is a tail call too, also at the end of if branches and match arms
+// the defaults of the args go before everything else in the body
+fn function_body(mut body: SpannedExpr, defaults: Vec<SpannedExpr>) -> SpannedExpr {
+    fn tail_calls(expr: &mut SpannedExpr) {
+        match &mut expr.node {
+            Expr::Call { .. } => {
+                let call = std::mem::replace(&mut expr.node, Expr::Null);
+                expr.node = Expr::Return(Box::new(SpannedExpr { node: call, span: expr.span }));
+            }
+
+            Expr::Block { value: Some(value), .. } => tail_calls(value),
+
+            Expr::If { then_branch, else_branch, .. } => {
+                tail_calls(then_branch);
+
+                if let Some(else_branch) = else_branch {
+                    tail_calls(else_branch);
+                }
+            }
+
+            Expr::Match { arms, .. } => {
+                for arm in arms {
+                    tail_calls(&mut arm.body);
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    tail_calls(&mut body);
+
+    if let Expr::Block { statements, .. } = &mut body.node {
+        statements.splice(0..0, defaults);
+    }
+
+    body
+}
+
 fn parser<'src>() -> impl Parser<
     'src, 
     &'src [(Token, Span)],
     Vec<SpannedExpr>,
     extra::Err<Rich<'src, (Token, Span), Span>>
 > {
-    let expr = recursive(|expr| {
+    // declared up front since expressions (like fn(x) { }) can contain blocks of statements
+    // and blocks can end with an expression
+    let mut stmt = Recursive::declare();
+    let mut expr = Recursive::declare();
+
+    let block = select! { (Token::LBrace, span) => span }
+        .then(stmt.clone().repeated().collect::<Vec<_>>())
+        .then(expr.clone().or_not())
+        .then(select! { (Token::RBrace, span) => span })
+        .map(|(((start, mut statements), mut value), end): (((Span, Vec<SpannedExpr>), Option<SpannedExpr>), Span)| {
+            // an if, match or block at the end doesnt need to leave out a semicolon to be the value
+            if value.is_none() && statements.last().is_some_and(|last| matches!(last.node, Expr::If { .. } | Expr::Match { .. } | Expr::Block { .. })) {
+                value = statements.pop();
+            }
+
+            SpannedExpr {
+                node: Expr::Block { statements, value: value.map(Box::new) },
+                span: Span::from(start.start..end.end),
+            }
+        })
+        .boxed();
+
+    let type_name = select! {
+        (Token::Identifier(name), span) => Spanned { node: Type::from_name(&name), span },
+        (Token::Null, span) => Spanned { node: Type::Null, span },
+    };
+
+    // (x: int, y = 10, ...rest) -> string, the types and defaults are optional and ...rest is last
+    let params = select! { (Token::Identifier(name), span) => (name, span) }
+        .then(select! { (Token::Colon, _) => () }.ignore_then(type_name).or_not())
+        .then(select! { (Token::Assign, _) => () }.ignore_then(expr.clone()).or_not())
+        .separated_by(select! { (Token::Comma, _) => () })
+        .allow_trailing()
+        .collect::<Vec<_>>()
+        .then(
+            select! { (Token::Ellipsis, _) => () }
+                .ignore_then(select! { (Token::Identifier(name), span) => (name, span) })
+                .then_ignore(select! { (Token::Comma, _) => () }.or_not())
+                .or_not()
+        )
+        // an arg without a default after one with a default would make that default unusable
+        .validate(|(named, rest): (Vec<_>, Option<(String, Span)>), _, emitter| {
+            let mut seen: Vec<&str> = vec![];
+            let mut defaulted = false;
+
+            for (((name, span), _), default) in &named {
+                if seen.contains(&name.as_str()) {
+                    emitter.emit(Rich::custom(*span, format!("Duplicate argument {}", name)));
+                }
+
+                if default.is_some() {
+                    defaulted = true;
+                } else if defaulted {
+                    emitter.emit(Rich::custom(*span, format!("Argument {} needs a default since an argument before it has one", name)));
+                }
+
+                seen.push(name);
+            }
+
+            if let Some((name, span)) = &rest && seen.contains(&name.as_str()) {
+                emitter.emit(Rich::custom(*span, format!("Duplicate argument {}", name)));
+            }
+
+            (named, rest.map(|(name, _)| name))
+        })
+        .delimited_by(select! { (Token::LParen, _) => () }, select! { (Token::RParen, _) => () })
+        .then(select! { (Token::Arrow, _) => () }.ignore_then(type_name).or_not())
+        .map(|((named, rest), returns): ((Vec<(_, Option<_>)>, _), _)| {
+            let min = named.iter().rposition(|(_, default)| default.is_none()).map_or(0, |last| last + 1);
+            let mut args = vec![];
+            let mut params = vec![];
+            let mut defaults = vec![];
+
+            for (((name, _), ty), default) in named {
+                if let Some(value) = default {
+                    defaults.push(SpannedExpr {
+                        span: value.span,
+                        node: Expr::Default { name: name.clone(), index: args.len(), value: Box::new(value) },
+                    });
+                }
+
+                args.push(name);
+                params.push(ty);
+            }
+
+            let arity = Arity { min, max: rest.is_none().then_some(args.len()) };
+
+            if let Some(rest) = rest {
+                args.push(rest);
+                params.push(None);
+            }
+
+            (args, arity, Signature { params, returns }, defaults)
+        })
+        .boxed();
+
+    // let [a, { b }, ...rest] = value;, the bindings of a for loop and the arms of a match
+    let pattern = recursive(|pattern| {
+        let binding = select! {
+            (Token::Identifier(name), span) if name == "_" => Spanned { node: Pattern::Wildcard, span },
+            (Token::Identifier(name), span) => Spanned { node: Pattern::Binding { name, slot: None }, span },
+        };
+
+        let minus = select! { (Token::Minus, span) => span }.or_not();
+
+        let int = minus
+            .then(select! { (Token::Int(n), span) => (n, span) })
+            .map(|(minus, (n, span)): (Option<Span>, (i64, Span))| match minus {
+                Some(minus) => (-n, Span::from(minus.start..span.end)),
+                None => (n, span),
+            });
+
+        let range = int
+            .then(select! {
+                (Token::Range, _) => false,
+                (Token::InclusiveRange, _) => true,
+            })
+            .then(int)
+            .map(|(((start, start_span), inclusive), (end, end_span))| Spanned {
+                node: Pattern::Range { start, end, inclusive },
+                span: Span::from(start_span.start..end_span.end),
+            });
+
+        let literal = choice((
+            int.map(|(n, span)| Spanned { node: Pattern::Literal(Value::Int(n)), span }),
+
+            minus
+                .then(select! { (Token::Float(f), span) => (f, span) })
+                .map(|(minus, (f, span)): (Option<Span>, (f64, Span))| match minus {
+                    Some(minus) => Spanned { node: Pattern::Literal(Value::Float(-f)), span: Span::from(minus.start..span.end) },
+                    None => Spanned { node: Pattern::Literal(Value::Float(f)), span },
+                }),
+
+            select! {
+                (Token::String(text), span) => Spanned { node: Pattern::Literal(Value::String(text.into())), span },
+                (Token::Bool(b), span) => Spanned { node: Pattern::Literal(Value::Bool(b)), span },
+                (Token::Null, span) => Spanned { node: Pattern::Literal(Value::Null), span },
+            },
+        ));
+
+        let array = select! { (Token::LBracket, span) => span }
+            .then(
+                pattern.clone()
+                    .separated_by(select! { (Token::Comma, _) => () })
+                    .allow_trailing()
+                    .collect::<Vec<_>>()
+            )
+            .then(
+                select! { (Token::Ellipsis, _) => () }
+                    .ignore_then(binding)
+                    .then_ignore(select! { (Token::Comma, _) => () }.or_not())
+                    .or_not()
+            )
+            .then(select! { (Token::RBracket, span) => span })
+            .map(|(((start, elements), rest), end): (((Span, _), _), Span)| Spanned {
+                node: Pattern::Array { elements, rest: rest.map(Box::new) },
+                span: Span::from(start.start..end.end),
+            });
+
+        let key = select! {
+            (Token::Identifier(name), span) => Spanned { node: name, span },
+            (Token::String(name), span) => Spanned { node: name, span },
+        };
+
+        let property = key
+            .then_ignore(select! { (Token::Colon, _) => () })
+            .then(pattern.clone())
+            .or(select! {
+                (Token::Identifier(name), span) => (
+                    Spanned { node: name.clone(), span },
+                    Spanned { node: Pattern::Binding { name, slot: None }, span },
+                )
+            });
+
+        let object = select! { (Token::LBrace, span) => span }
+            .then(
+                property
+                    .separated_by(select! { (Token::Comma, _) => () })
+                    .allow_trailing()
+                    .collect::<Vec<_>>()
+            )
+            .then(select! { (Token::RBrace, span) => span })
+            .map(|((start, properties), end): ((Span, _), Span)| Spanned {
+                node: Pattern::Object { properties },
+                span: Span::from(start.start..end.end),
+            });
+
+        let variant = select! { (Token::Identifier(name), span) => (name, span) }
+            .then_ignore(select! { (Token::Dot, _) => () })
+            .then(select! { (Token::Identifier(name), span) => Spanned { node: name, span } })
+            .then(
+                select! { (Token::LParen, _) => () }
+                    .ignore_then(
+                        pattern.clone()
+                            .separated_by(select! { (Token::Comma, _) => () })
+                            .allow_trailing()
+                            .collect::<Vec<_>>()
+                    )
+                    .then(select! { (Token::RParen, span) => span })
+                    .or_not()
+            )
+            .map(|(((enum_name, start), variant), values): (((String, Span), Spanned<String>), _)| {
+                let end = values.as_ref().map_or(variant.span, |(_, end): &(_, Span)| *end);
+
+                Spanned {
+                    node: Pattern::Variant { enum_name, variant, values: values.map(|(values, _)| values) },
+                    span: Span::from(start.start..end.end),
+                }
+            });
+
+        choice((range, literal, variant, binding, array, object))
+            .separated_by(select! { (Token::Pipe, _) => () })
+            .at_least(1)
+            .collect::<Vec<_>>()
+            .map(|mut alternatives: Vec<Spanned<Pattern>>| {
+                if alternatives.len() == 1 {
+                    return alternatives.pop().unwrap();
+                }
+
+                Spanned {
+                    span: Span::from(alternatives[0].span.start..alternatives[alternatives.len() - 1].span.end),
+                    node: Pattern::Or(alternatives),
+                }
+            })
+    })
+    .boxed();
+
+    // if and match can be used both as expressions and as statements
+    let if_expr = recursive(|if_expr| {
+        select! { (Token::If, span) => span }
+            .then(expr.clone())
+            .then(block.clone())
+            .then(
+                select! { (Token::Else, _) => () }
+                    .ignore_then(block.clone().or(if_expr))
+                    .or_not()
+            )
+            .map(|(((start, condition), then_branch), else_branch): (((Span, SpannedExpr), SpannedExpr), Option<SpannedExpr>)| SpannedExpr {
+                span: Span::from(start.start..else_branch.as_ref().map_or(then_branch.span.end, |eb| eb.span.end)),
+                node: Expr::If {
+                    condition: Box::new(condition),
+                    then_branch: Box::new(then_branch),
+                    else_branch: else_branch.map(Box::new),
+                },
+            })
+    })
+    .boxed();
+
+    let match_arm = pattern.clone()
+        .then(
+            select! { (Token::If, _) => () }
+                .ignore_then(expr.clone())
+                .or_not()
+        )
+        .then_ignore(select! { (Token::FatArrow, _) => () })
+        .then(block.clone().or(expr.clone()))
+        .then_ignore(select! { (Token::Comma, _) => () }.or_not())
+        .map(|((pattern, guard), body)| MatchArm { pattern, guard, body });
+
+    let match_expr = select! { (Token::Match, span) => span }
+        .then(expr.clone())
+        .then_ignore(select! { (Token::LBrace, _) => () })
+        .then(match_arm.repeated().collect::<Vec<_>>())
+        .then(select! { (Token::RBrace, span) => span })
+        .map(|(((start, value), arms), end): (((Span, SpannedExpr), Vec<MatchArm>), Span)| SpannedExpr {
+            node: Expr::Match { value: Box::new(value), arms },
+            span: Span::from(start.start..end.end),
+        })
+        .boxed();
+
+    expr.define({
         let atom = select! {
             (Token::Int(n), span) => SpannedExpr { node: Expr::Int(n), span },
             (Token::Float(f), span) => SpannedExpr { node: Expr::Float(f), span },
@@ -36,10 +432,44 @@ fn parser<'src>() -> impl Parser<
             (Token::Identifier(name), span) => SpannedExpr { node: Expr::Identifier(name), span },
             (Token::Bool(b), span) => SpannedExpr { node: Expr::Bool(b), span },
             (Token::Null, span) => SpannedExpr { node: Expr::Null, span },
-            (Token::Break, span) => SpannedExpr { node: Expr::Break, span },
-            (Token::Continue, span) => SpannedExpr { node: Expr::Continue, span },
         };
 
+        let interpolation = select! { (Token::FStringStart, span) => span }
+            .then(
+                select! { (Token::String(text), span) => SpannedExpr { node: Expr::String(text), span } }
+                    .or(
+                        select! { (Token::LBrace, _) => () }
+                            .ignore_then(expr.clone())
+                            .then_ignore(select! { (Token::RBrace, _) => () })
+                    )
+                    .repeated()
+                    .collect::<Vec<_>>()
+            )
+            .then(select! { (Token::FStringEnd, span) => span })
+            .map(|((start, parts), end): ((Span, Vec<SpannedExpr>), Span)| SpannedExpr {
+                node: Expr::Interpolation(parts),
+                span: Span::from(start.start..end.end),
+            });
+
+        let loop_control = select! {
+                (Token::Break, span) => (Token::Break, span),
+                (Token::Continue, span) => (Token::Continue, span),
+            }
+            .then(select! { (Token::Label(name), span) => (name, span) }.or_not())
+            .map(|((kind, start), label): ((Token, Span), Option<(String, Span)>)| {
+                let end = label.as_ref().map_or(start.end, |(_, span)| span.end);
+                let label = label.map(|(name, _)| name);
+
+                SpannedExpr {
+                    node: match kind {
+                        Token::Break => Expr::Break(label),
+                        Token::Continue => Expr::Continue(label),
+                        _ => unreachable!(),
+                    },
+                    span: Span::from(start.start..end),
+                }
+            });
+
         let array = select! { (Token::LBracket, span) => span }
             .then(
                 expr.clone()
@@ -53,9 +483,61 @@ fn parser<'src>() -> impl Parser<
                 span: Span::from(start.start..end.end),
             });
 
+        let object_key = choice((
+            select! {
+                (Token::Identifier(name), span) => SpannedExpr { node: Expr::String(name), span },
+                (Token::String(name), span) => SpannedExpr { node: Expr::String(name), span },
+            },
+
+            select! { (Token::LBracket, _) => () }
+                .ignore_then(expr.clone())
+                .then_ignore(select! { (Token::RBracket, _) => () }),
+        ));
+
+        let object_entry = object_key
+            .then_ignore(select! { (Token::Colon, _) => () })
+            .then(expr.clone())
+            .or(select! {
+                (Token::Identifier(name), span) => (
+                    SpannedExpr { node: Expr::String(name.clone()), span },
+                    SpannedExpr { node: Expr::Identifier(name), span },
+                )
+            });
+
+        // only reachable in expression position, so a `{` at the start of a
+        // statement is still parsed as a block first, and in expression position a
+        // `{` is only a block when what it holds cant be object entries, like { let t = 3; t * 2 }
+        let object = select! { (Token::LBrace, span) => span }
+            .then(
+                object_entry
+                    .separated_by(select! { (Token::Comma, _) => () })
+                    .allow_trailing()
+                    .collect::<Vec<_>>()
+            )
+            .then(select! { (Token::RBrace, span) => span })
+            .map(|((start, properties), end): ((Span, Vec<(SpannedExpr, SpannedExpr)>), Span)| SpannedExpr {
+                node: Expr::ObjectLiteral { properties },
+                span: Span::from(start.start..end.end),
+            });
+
+        let lambda = select! { (Token::Function, span) => span }
+            .then(params.clone())
+            .then(block.clone())
+            .map(|((start, (args, arity, signature, defaults)), body): ((Span, _), SpannedExpr)| SpannedExpr {
+                span: Span::from(start.start..body.span.end),
+                node: Expr::Lambda { args, arity, signature, body: Rc::new(function_body(body, defaults)), frame_size: 0 },
+            });
+
         let primary = choice((
             atom,
+            if_expr.clone(),
+            match_expr.clone(),
+            interpolation,
+            lambda,
+            loop_control,
             array,
+            object,
+            block.clone(),
             select! { (Token::LParen, _) => () }
                 .ignore_then(expr.clone())
                 .then_ignore(select! { (Token::RParen, _) => () })
@@ -68,15 +550,25 @@ fn parser<'src>() -> impl Parser<
                         .then(select! { (Token::Identifier(name), span) => (name, span) })
                         .map(|(_, (name, span))| Postfix::Property(name, span)),
                     
+                    // f(x, timeout: 5), named args after the others
                     select! { (Token::LParen, _) => () }
                         .ignore_then(
                             expr.clone()
+                                .then_ignore(select! { (Token::Colon, _) => () }.not())
+                                .separated_by(select! { (Token::Comma, _) => () })
+                                .allow_trailing()
+                                .collect::<Vec<_>>()
+                        )
+                        .then(
+                            select! { (Token::Identifier(name), span) => Spanned { node: name, span } }
+                                .then_ignore(select! { (Token::Colon, _) => () })
+                                .then(expr.clone())
                                 .separated_by(select! { (Token::Comma, _) => () })
                                 .allow_trailing()
                                 .collect::<Vec<_>>()
                         )
                         .then_ignore(select! { (Token::RParen, span) => span })
-                        .map(Postfix::Call),
+                        .map(|(args, named)| Postfix::Call(args, named)),
 
                     select! { (Token::LBracket, _) => () }
                         .ignore_then(expr.clone())
@@ -92,11 +584,12 @@ fn parser<'src>() -> impl Parser<
                         span: Span::from(obj.span.start..span.end),
                     },
 
-                    Postfix::Call(args) => SpannedExpr {
+                    Postfix::Call(args, named) => SpannedExpr {
                         span: obj.span.clone(),
                         node: Expr::Call {
                             callee: Box::new(obj.clone()),
                             args,
+                            named,
                         },
                     },
 
@@ -108,28 +601,59 @@ fn parser<'src>() -> impl Parser<
                         },
                     },
                 },
-            );
+            )
+            .boxed();
 
-        let unary = select! { (Token::Minus, span) => span }
+        let unary = choice((
+                select! { (Token::Minus, span) => (Token::Minus, span) },
+                select! { (Token::Bang, span) => (Token::Bang, span) },
+            ))
             .repeated()
-            .collect::<Vec<Span>>()
+            .collect::<Vec<(Token, Span)>>()
             .then(postfix)
-            .map(|(neg, mut expr): (Vec<Span>, SpannedExpr)| {
-                for neg_span in neg.into_iter().rev() {
+            .map(|(ops, mut expr): (Vec<(Token, Span)>, SpannedExpr)| {
+                for (op, op_span) in ops.into_iter().rev() {
                     expr = SpannedExpr {
-                        node: Expr::Neg(Box::new(expr.clone())),
-                        span: Span::from(neg_span.start..expr.span.end),
+                        node: match op {
+                            Token::Minus => Expr::Neg(Box::new(expr.clone())),
+                            Token::Bang => Expr::Not(Box::new(expr.clone())),
+                            _ => unreachable!(),
+                        },
+                        span: Span::from(op_span.start..expr.span.end),
                     };
                 }
 
                 expr
-            });
+            })
+            .boxed();
+
+        // `*` is only a multiplication here, `import ... as *` is handled by import_stmt
+        let multiplicative = unary.clone()
+            .foldl(
+                choice((
+                    select! { (Token::Star, span) => span }.then(unary.clone()).map(|(span, right)| (Token::Star, span, right)),
+                    select! { (Token::Slash, span) => span }.then(unary.clone()).map(|(span, right)| (Token::Slash, span, right)),
+                    select! { (Token::Percent, span) => span }.then(unary.clone()).map(|(span, right)| (Token::Percent, span, right)),
+                ))
+                .repeated(),
+
+                |left: SpannedExpr, (op, _span, right): (Token, Span, SpannedExpr)| SpannedExpr {
+                    node: match op {
+                        Token::Star => Expr::Mul(Box::new(left.clone()), Box::new(right.clone())),
+                        Token::Slash => Expr::Div(Box::new(left.clone()), Box::new(right.clone())),
+                        Token::Percent => Expr::Mod(Box::new(left.clone()), Box::new(right.clone())),
+                        _ => unreachable!(),
+                    },
+                    span: Span::from(left.span.start..right.span.end),
+                }
+            )
+            .boxed();
 
-        let additive = unary.clone()
+        let additive = multiplicative.clone()
             .foldl(
                 choice((
-                    select! { (Token::Plus, span) => span }.then(unary.clone()).map(|(span, right)| (Token::Plus, span, right)),
-                    select! { (Token::Minus, span) => span }.then(unary.clone()).map(|(span, right)| (Token::Minus, span, right)),
+                    select! { (Token::Plus, span) => span }.then(multiplicative.clone()).map(|(span, right)| (Token::Plus, span, right)),
+                    select! { (Token::Minus, span) => span }.then(multiplicative.clone()).map(|(span, right)| (Token::Minus, span, right)),
                 ))
                 .repeated(),
 
@@ -141,7 +665,8 @@ fn parser<'src>() -> impl Parser<
                     },
                     span: Span::from(left.span.start..right.span.end),
                 }
-            );
+            )
+            .boxed();
         
         let range = additive.clone()
             .then(
@@ -161,7 +686,8 @@ fn parser<'src>() -> impl Parser<
 
                     None => start,
                 }
-            });
+            })
+            .boxed();
         
         let inclusive_range = range.clone()
             .then(
@@ -180,9 +706,10 @@ fn parser<'src>() -> impl Parser<
                     },
                     None => start,
                 }
-            });
+            })
+            .boxed();
         
-        inclusive_range.clone()
+        let comparison = inclusive_range.clone()
             .foldl(
                 choice((
                     select! { (Token::DoubleEqual, span) => span }.then(inclusive_range.clone()).map(|(span, right)| (Token::DoubleEqual, span, right)),
@@ -205,20 +732,75 @@ fn parser<'src>() -> impl Parser<
                     },
                     span: Span::from(left.span.start..right.span.end),
                 }
-            ) 
+            )
+            .boxed();
+
+        let and = comparison.clone()
+            .foldl(
+                select! { (Token::And, span) => span }.then(comparison.clone()).repeated(),
+
+                |left: SpannedExpr, (_span, right): (Span, SpannedExpr)| SpannedExpr {
+                    span: Span::from(left.span.start..right.span.end),
+                    node: Expr::And(Box::new(left), Box::new(right)),
+                }
+            )
+            .boxed();
+
+        and.clone()
+            .foldl(
+                select! { (Token::Or, span) => span }.then(and.clone()).repeated(),
+
+                |left: SpannedExpr, (_span, right): (Span, SpannedExpr)| SpannedExpr {
+                    span: Span::from(left.span.start..right.span.end),
+                    node: Expr::Or(Box::new(left), Box::new(right)),
+                }
+            )
     });
 
-    let stmt = recursive(|stmt| {
+    stmt.define({
+        // only a plain name can have a type, let x: int = 1;
+        let annotated = select! { (Token::Identifier(name), span) => Spanned { node: Pattern::Binding { name, slot: None }, span } }
+            .then_ignore(select! { (Token::Colon, _) => () })
+            .then(type_name.map(Some));
+
         let let_stmt = select! { (Token::Let, span) => span }
-            .then(select! { (Token::Identifier(name), _) => name })
+            .then(annotated.or(pattern.clone().map(|pattern| (pattern, None))))
             .then_ignore(select! { (Token::Assign, _) => () })
             .then(expr.clone())
             .then(select! { (Token::Semicolon, span) => span }.labelled("semicolon"))
-            .map(|(((start, name), value), end): (((Span, String), SpannedExpr), Span)| SpannedExpr {
-                node: Expr::Let { name, value: Box::new(value) },
+            .map(|(((start, (pattern, annotation)), value), end): (((Span, _), _), Span)| SpannedExpr {
+                node: match pattern.node {
+                    Pattern::Binding { name, .. } => Expr::Let { name, value: Box::new(value), slot: None, annotation },
+                    _ => Expr::Destructure { pattern, value: Box::new(value) },
+                },
                 span: Span::from(start.start..end.end),
             });
 
+        // the target is parsed as a normal expression, eval checks that it is
+        // actually something that can be assigned to (a.b[0].c etc)
+        let assign_stmt = expr.clone()
+            .then(select! {
+                (Token::Assign, _) => None,
+                (Token::PlusAssign, _) => Some(BinaryOp::Add),
+                (Token::MinusAssign, _) => Some(BinaryOp::Sub),
+                (Token::StarAssign, _) => Some(BinaryOp::Mul),
+                (Token::SlashAssign, _) => Some(BinaryOp::Div),
+                (Token::PercentAssign, _) => Some(BinaryOp::Mod),
+            })
+            .then(expr.clone())
+            .then(select! { (Token::Semicolon, span) => span }.labelled("semicolon"))
+            .map(|(((target, op), value), end): (_, Span)| {
+                let (target, value) = (Box::new(target), Box::new(value));
+
+                SpannedExpr {
+                    span: Span::from(target.span.start..end.end),
+                    node: match op {
+                        Some(op) => Expr::CompoundAssign { op, target, value },
+                        None => Expr::Assign { target, value },
+                    },
+                }
+            });
+
         let expr_stmt = expr.clone()
             .map_with(|expr, e| (expr, e.span()))
             .then(select! { (Token::Semicolon, span) => span }.labelled("semicolon"))
@@ -230,74 +812,160 @@ fn parser<'src>() -> impl Parser<
             })
             .labelled("statement");
         
-        let block = select! { (Token::LBrace, span) => span }
-            .then(stmt.clone().repeated().collect::<Vec<_>>())
+        let fn_stmt = select! { (Token::Function, span) => span }
+            .then(select! { (Token::Identifier(name), _) => name })
+            .then(params.clone())
+            .then(block.clone())
+            .map(|(((start, name), (args, arity, signature, defaults)), body): (((Span, _), _), SpannedExpr)| SpannedExpr {
+                node: Expr::Function { name, args, arity, signature, body: Rc::new(function_body(body.clone(), defaults)), slot: None, frame_size: 0 },
+                span: Span::from(start.start..body.span.end),
+            });
+
+        // the fields come first, separated by commas, then the methods
+        let struct_stmt = select! { (Token::Struct, span) => span }
+            .then(select! { (Token::Identifier(name), _) => name })
+            .then_ignore(select! { (Token::LBrace, _) => () })
+            .then(
+                select! { (Token::Identifier(name), _) => name }
+                    .separated_by(select! { (Token::Comma, _) => () })
+                    .allow_trailing()
+                    .collect::<Vec<_>>()
+            )
+            .then(fn_stmt.clone().repeated().collect::<Vec<_>>())
             .then(select! { (Token::RBrace, span) => span })
-            .map(|((start, stmts), end): ((Span, Vec<SpannedExpr>), Span)| SpannedExpr {
-                node: Expr::Block(stmts),
-                span: Span::from(start.start..end.end),
+            .map(|((((start, name), fields), methods), end): ((((Span, _), _), _), Span)| {
+                let methods = methods.into_iter()
+                    .map(|method| match method.node {
+                        Expr::Function { name, args, arity, signature, body, .. } => Method { name, args, arity, signature, body, frame_size: 0 },
+                        _ => unreachable!(),
+                    })
+                    .collect();
+
+                SpannedExpr {
+                    node: Expr::Struct { name, fields, methods, slot: None },
+                    span: Span::from(start.start..end.end),
+                }
             });
         
-        let fn_stmt = select! { (Token::Function, span) => span }
+        // enum Shape { Circle(r), Rect(w, h), Empty }
+        let enum_stmt = select! { (Token::Enum, span) => span }
             .then(select! { (Token::Identifier(name), _) => name })
-            .then_ignore(select! { (Token::LParen, _) => () })
+            .then_ignore(select! { (Token::LBrace, _) => () })
             .then(
                 select! { (Token::Identifier(name), _) => name }
+                    .then(
+                        select! { (Token::Identifier(name), _) => name }
+                            .separated_by(select! { (Token::Comma, _) => () })
+                            .allow_trailing()
+                            .collect::<Vec<_>>()
+                            .delimited_by(select! { (Token::LParen, _) => () }, select! { (Token::RParen, _) => () })
+                            .or_not()
+                            .map(Option::unwrap_or_default)
+                    )
                     .separated_by(select! { (Token::Comma, _) => () })
                     .allow_trailing()
                     .collect::<Vec<_>>()
             )
-            .then_ignore(select! { (Token::RParen, _) => () })
-            .then(block.clone())
-            .map(|(((start, name), args), body): (((Span, String), Vec<String>), SpannedExpr)| SpannedExpr {
-                node: Expr::Function { name, args, body: Box::new(body.clone()) },
-                span: Span::from(start.start..body.span.end),
+            .then(select! { (Token::RBrace, span) => span })
+            .map(|(((start, name), variants), end): (((Span, String), _), Span)| SpannedExpr {
+                node: Expr::Enum { name, variants, slot: None },
+                span: Span::from(start.start..end.end),
             });
-        
+
         let infinite_loop_stmt = select! { (Token::Loop, span) => span }
             .then(block.clone())
             .map(|(start, body): (Span, SpannedExpr)| SpannedExpr {
-                node: Expr::InfiniteLoop { body: Box::new(body.clone()) },
+                node: Expr::InfiniteLoop { body: Box::new(body.clone()), label: None },
                 span: Span::from(start.start..body.span.end),
             });
         
+        // for i, x = ... binds the index to the first name
+        let for_bindings = select! { (Token::Identifier(name), _) => name }
+            .then_ignore(select! { (Token::Comma, _) => () })
+            .or_not()
+            .then(pattern.clone());
+
+        // for [k, v] = pairs { body } becomes for item = pairs { let [k, v] = item; body }
+        // the name cant be written in a script, so it never clashes with a real variable
         let for_loop_stmt = select! { (Token::For, span) => span }
-            .then(select! { (Token::Identifier(name), _) => name })
+            .then(for_bindings)
             .then_ignore(select! { (Token::Assign, _) => () })
             .then(expr.clone())
             .then(block.clone())
-            .map(|(((start, iterator_name), iterator_range), body): (((Span, String), SpannedExpr), SpannedExpr)| SpannedExpr {
-                node: Expr::ForLoop {
-                    iterator_name,
-                    iterator_range: Box::new(iterator_range.clone()),
-                    body: Box::new(body.clone()),
-                },
-                span: Span::from(start.start..body.span.end),
+            .map(|(((start, (index_name, pattern)), iterator_range), body): (((Span, _), SpannedExpr), SpannedExpr)| {
+                let (iterator_name, body) = match pattern.node {
+                    Pattern::Binding { name, .. } => (name, body),
+
+                    _ => {
+                        let item = "<item>".to_string();
+                        let span = body.span;
+
+                        let destructure = SpannedExpr {
+                            span: pattern.span,
+                            node: Expr::Destructure {
+                                value: Box::new(SpannedExpr { node: Expr::Identifier(item.clone()), span: pattern.span }),
+                                pattern,
+                            },
+                        };
+
+                        (item, SpannedExpr { node: Expr::Block { statements: vec![destructure, body], value: None }, span })
+                    }
+                };
+
+                SpannedExpr {
+                    span: Span::from(start.start..body.span.end),
+                    node: Expr::ForLoop {
+                        iterator_name,
+                        index_name,
+                        iterator_slot: None,
+                        index_slot: None,
+                        iterator_range: Box::new(iterator_range),
+                        body: Box::new(body),
+                        label: None,
+                        scope_size: None,
+                    },
+                }
             });
-        
-        let if_stmt = select! { (Token::If, span) => span }
+
+        let while_loop_stmt = select! { (Token::While, span) => span }
             .then(expr.clone())
             .then(block.clone())
-            .then(
-                select! { (Token::Else, span) => span }
-                    .then(block.clone())
-                    .or_not()
-            )
-            .map(|(((start, condition), then_branch), else_branch): (((Span, SpannedExpr), SpannedExpr), Option<(Span, SpannedExpr)>)| SpannedExpr {
-                node: Expr::If {
-                    condition: Box::new(condition.clone()),
-                    then_branch: Box::new(then_branch.clone()),
-                    else_branch: else_branch.clone().map(|(_, eb)| Box::new(eb.clone())),
+            .map(|((start, condition), body): ((Span, SpannedExpr), SpannedExpr)| SpannedExpr {
+                node: Expr::WhileLoop {
+                    condition: Box::new(condition),
+                    body: Box::new(body.clone()),
+                    label: None,
                 },
+                span: Span::from(start.start..body.span.end),
+            });
 
-                span: Span::from(start.start..{
-                    match &else_branch {
-                        Some((_, eb)) => eb.span.end,
-                        None => then_branch.span.end,
+        let loop_stmt = select! { (Token::Label(name), span) => (name, span) }
+            .then_ignore(select! { (Token::Colon, _) => () })
+            .or_not()
+            .then(choice((infinite_loop_stmt, for_loop_stmt, while_loop_stmt)))
+            .map(|(loop_label, mut stmt): (Option<(String, Span)>, SpannedExpr)| {
+                if let Some((name, start)) = loop_label {
+                    match &mut stmt.node {
+                        Expr::InfiniteLoop { label, .. }
+                        | Expr::ForLoop { label, .. }
+                        | Expr::WhileLoop { label, .. } => *label = Some(name),
+                        _ => unreachable!(),
                     }
-                }),
+
+                    stmt.span = Span::from(start.start..stmt.span.end);
+                }
+
+                stmt
             });
         
+        // on their own if and match dont need a semicolon
+        let if_stmt = choice((if_expr.clone(), match_expr.clone()))
+            .then(select! { (Token::Semicolon, span) => span }.or_not())
+            .map(|(expr, end): (SpannedExpr, Option<Span>)| SpannedExpr {
+                span: Span::from(expr.span.start..end.map_or(expr.span.end, |end| end.end)),
+                node: expr.node,
+            });
+
         let retun_stmt = select! { (Token::Return, span) => span }
             .then(expr.clone().or_not())
             .then(select! { (Token::Semicolon, span) => span }.labelled("semicolon"))
@@ -314,6 +982,53 @@ fn parser<'src>() -> impl Parser<
                 span: Span::from(start.start..end.end),
             });
         
+        let throw_stmt = select! { (Token::Throw, span) => span }
+            .then(expr.clone())
+            .then(select! { (Token::Semicolon, span) => span }.labelled("semicolon"))
+            .map(|((start, value), end): ((Span, SpannedExpr), Span)| SpannedExpr {
+                node: Expr::Throw(Box::new(value)),
+                span: Span::from(start.start..end.end),
+            });
+
+        let catch_clause = select! { (Token::Catch, _) => () }
+            .ignore_then(select! { (Token::Identifier(name), _) => name }.or_not())
+            .then(block.clone());
+
+        let finally_clause = select! { (Token::Finally, _) => () }
+            .ignore_then(block.clone());
+
+        let try_stmt = select! { (Token::Try, span) => span }
+            .then(block.clone())
+            .then(
+                catch_clause.clone()
+                    .map(Some)
+                    .then(finally_clause.clone().or_not())
+                    .or(finally_clause.map(|finally_body| (None, Some(finally_body))))
+            )
+            .map(|((start, body), (catch, finally_body)): ((Span, SpannedExpr), TryClauses)| {
+                let end = match (&catch, &finally_body) {
+                    (_, Some(finally_body)) => finally_body.span.end,
+                    (Some((_, catch_body)), None) => catch_body.span.end,
+                    (None, None) => body.span.end,
+                };
+
+                let (catch_name, catch_body) = match catch {
+                    Some((name, catch_body)) => (name, Some(Box::new(catch_body))),
+                    None => (None, None),
+                };
+
+                SpannedExpr {
+                    node: Expr::Try {
+                        body: Box::new(body),
+                        catch_name,
+                        catch_slot: None,
+                        catch_body,
+                        finally_body: finally_body.map(Box::new),
+                    },
+                    span: Span::from(start.start..end),
+                }
+            });
+
         let import_stmt = select! { (Token::Import, span) => span }
             .then(expr.clone())
             .then(
@@ -342,11 +1057,15 @@ fn parser<'src>() -> impl Parser<
                 
         let_stmt
             .or(fn_stmt)
-            .or(infinite_loop_stmt)
-            .or(for_loop_stmt)
+            .or(struct_stmt)
+            .or(enum_stmt)
+            .or(loop_stmt)
             .or(if_stmt)
             .or(import_stmt)
             .or(retun_stmt)
+            .or(throw_stmt)
+            .or(try_stmt)
+            .or(assign_stmt)
             .or(block)
             .or(expr_stmt)
     });
@@ -354,7 +1073,8 @@ fn parser<'src>() -> impl Parser<
     stmt.repeated().collect::<Vec<_>>().then_ignore(end())
 }
 
-pub fn parse(input: &str, filename: &str, context: &mut HashMap<String, Expr>) {
+// lexes and parses a file, reporting any errors, used by modu run and modu check
+fn parse_ast(input: &str, filename: &str) -> Option<Vec<SpannedExpr>> {
     let tokens = match lex(input) {
         Ok(toks) => toks,
         Err(e) => {
@@ -370,98 +1090,12 @@ pub fn parse(input: &str, filename: &str, context: &mut HashMap<String, Expr>) {
             
             report_error(report, filename, input);
 
-            return;
+            return None;
         }
     };
 
     match parser().parse(&tokens).into_result() {
-        Ok(ast) => {
-            let sys_args = std::env::args().collect::<Vec<String>>();
-
-            if sys_args.contains(&"--debug".to_string()) {
-                println!("AST: {:#?}", ast);
-            }
-
-            for expr in ast {
-                match &expr.node {
-                    // should never be an return in the top-level
-                    Expr::Return(_) => {
-                        let report = Report::build(ReportKind::Error, (filename, expr.span.into_range()))
-                            .with_code(3)
-                            .with_message("Return statement not allowed in top-level")
-                            .with_label(
-                                Label::new((filename, expr.span.into_range()))
-                                    .with_color(Color::Red)
-                                    .with_message("unexpected return statement"),
-                            )
-                            .with_help("Return statements can only be used inside functions")
-                            .finish();
-                        
-                        report_error(report, filename, input);
-
-                        return;
-                    }
-
-                    Expr::Break => {
-                        let report = Report::build(ReportKind::Error, (filename, expr.span.into_range()))
-                            .with_code(4)
-                            .with_message("Break statement not allowed in top-level")
-                            .with_label(
-                                Label::new((filename, expr.span.into_range()))
-                                    .with_color(Color::Red)
-                                    .with_message("unexpected break statement"),
-                            )
-                            .with_help("Break statements can only be used inside loops")
-                            .finish();
-                        
-                        report_error(report, filename, input);
-
-                        return;
-                    }
-
-                    Expr::Continue => {
-                        let report = Report::build(ReportKind::Error, (filename, expr.span.into_range()))
-                            .with_code(5)
-                            .with_message("Continue statement not allowed in top-level")
-                            .with_label(
-                                Label::new((filename, expr.span.into_range()))
-                                    .with_color(Color::Red)
-                                    .with_message("unexpected continue statement"),
-                            )
-                            .with_help("Continue statements can only be used inside loops")
-                            .finish();
-                        
-                        report_error(report, filename, input);
-
-                        return;
-                    }
-
-                    _ => {}
-                }
-
-                match eval::eval(&expr, context) {
-                    Ok(_) => {
-                        
-                    }
-
-                    Err(e) => {
-                        let report = Report::build(ReportKind::Error, (filename, e.span.into_range()))
-                            .with_code(1)
-                            .with_message(format!("Evaluation error: {}", e.message))
-                            .with_label(
-                                Label::new((filename, e.span.into_range()))
-                                    .with_color(Color::Red)
-                                    .with_message(format!("{}", e.message_short)),
-                            )
-                            .finish();
-                        
-                        report_error(report, filename, input);
-
-                        return;
-                    }
-                }
-            }
-        }
+        Ok(ast) => Some(ast),
 
         Err(e) => {
             for err in e {
@@ -494,9 +1128,10 @@ pub fn parse(input: &str, filename: &str, context: &mut HashMap<String, Expr>) {
                         report_error(report, filename, input);
                     }
     
-                    _ => {
+                    chumsky::error::RichReason::Custom(message) => {
                         let report = Report::build(ReportKind::Error, (filename, span.into_range()))
-                            .with_message(format!("{:?}", err.reason()))
+                            .with_code(2)
+                            .with_message(message)
                             .with_label(
                                     Label::new((filename, span.into_range()))
                                         .with_color(Color::Red)
@@ -508,6 +1143,156 @@ pub fn parse(input: &str, filename: &str, context: &mut HashMap<String, Expr>) {
                     }
                } 
             }
+
+            None
+        }
+    }
+}
+
+// modu check, returns whether the file is free of syntax and type errors
+pub fn check(input: &str, filename: &str) -> bool {
+    let Some(ast) = parse_ast(input, filename) else {
+        return false;
+    };
+
+    let errors = checker::check(&ast);
+
+    for e in &errors {
+        let mut report = Report::build(ReportKind::Error, (filename, e.span.into_range()))
+            .with_code(7)
+            .with_message(format!("Type error: {}", e.message))
+            .with_label(
+                Label::new((filename, e.span.into_range()))
+                    .with_color(Color::Red)
+                    .with_message(&e.message_short),
+            );
+
+        if let Some(expected_at) = e.expected_at {
+            report.add_label(
+                Label::new((filename, expected_at.into_range()))
+                    .with_color(Color::Yellow)
+                    .with_message("expected because of this"),
+            );
+        }
+
+        report_error(report.finish(), filename, input);
+    }
+
+    errors.is_empty()
+}
+
+pub fn parse(input: &str, filename: &str, context: &Globals) {
+    let Some(mut ast) = parse_ast(input, filename) else {
+        return;
+    };
+
+    let known_globals = context.borrow().keys().cloned().collect();
+    let frame_size = match resolver::resolve(&mut ast, known_globals) {
+        Ok(frame_size) => frame_size,
+
+        Err(labels) => {
+            for label in labels {
+                let report = Report::build(ReportKind::Error, (filename, label.span.into_range()))
+                    .with_code(6)
+                    .with_message(format!("No loop labeled '{} around this statement", label.node))
+                    .with_label(
+                        Label::new((filename, label.span.into_range()))
+                            .with_color(Color::Red)
+                            .with_message(format!("unknown label '{}", label.node)),
+                    )
+                    .with_help("Labels can only refer to loops that contain the break or continue")
+                    .finish();
+
+                report_error(report, filename, input);
+            }
+
+            return;
+        }
+    };
+    let source = Rc::new(env::Source { name: filename.to_string(), text: input.to_string() });
+    let env = Env::new(context.clone(), frame_size, source.clone());
+
+    let sys_args = std::env::args().collect::<Vec<String>>();
+
+    if sys_args.contains(&"--debug".to_string()) {
+        println!("AST: {:#?}", ast);
+    }
+
+    let use_vm = sys_args.contains(&"--vm".to_string());
+
+    for expr in ast {
+        match &expr.node {
+            // should never be an return in the top-level
+            Expr::Return(_) => {
+                let report = Report::build(ReportKind::Error, (filename, expr.span.into_range()))
+                    .with_code(3)
+                    .with_message("Return statement not allowed in top-level")
+                    .with_label(
+                        Label::new((filename, expr.span.into_range()))
+                            .with_color(Color::Red)
+                            .with_message("unexpected return statement"),
+                    )
+                    .with_help("Return statements can only be used inside functions")
+                    .finish();
+                
+                report_error(report, filename, input);
+
+                return;
+            }
+
+            Expr::Break(_) => {
+                let report = Report::build(ReportKind::Error, (filename, expr.span.into_range()))
+                    .with_code(4)
+                    .with_message("Break statement not allowed in top-level")
+                    .with_label(
+                        Label::new((filename, expr.span.into_range()))
+                            .with_color(Color::Red)
+                            .with_message("unexpected break statement"),
+                    )
+                    .with_help("Break statements can only be used inside loops")
+                    .finish();
+                
+                report_error(report, filename, input);
+
+                return;
+            }
+
+            Expr::Continue(_) => {
+                let report = Report::build(ReportKind::Error, (filename, expr.span.into_range()))
+                    .with_code(5)
+                    .with_message("Continue statement not allowed in top-level")
+                    .with_label(
+                        Label::new((filename, expr.span.into_range()))
+                            .with_color(Color::Red)
+                            .with_message("unexpected continue statement"),
+                    )
+                    .with_help("Continue statements can only be used inside loops")
+                    .finish();
+                
+                report_error(report, filename, input);
+
+                return;
+            }
+
+            _ => {}
+        }
+
+        let result = if use_vm {
+            vm::eval(&expr, &env)
+        } else {
+            eval::eval(&expr, &env).and_then(|flow| eval::finish_tail_call(flow, &env)).map_err(|error| *error)
+        };
+
+        match result {
+            Ok(_) => {
+                
+            }
+
+            Err(e) => {
+                report_eval_error(&e, source);
+
+                return;
+            }
         }
     }
 }
\ No newline at end of file
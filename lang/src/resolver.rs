@@ -0,0 +1,455 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::ast::{Expr, Pattern, SpannedExpr, Spanned};
+use crate::env::Slot;
+use crate::lexer::Span;
+
+// runs once over the ast before it is evaluated and turns every local variable
+// into a slot in a frame, so eval never has to look locals up by name
+//
+// the outermost level of a script is the only place globals are made, everything
+// declared inside a block or function becomes a local of the enclosing function
+// (the script counts as a function too, so blocks at the top level get slots as well)
+
+struct FunctionScope {
+    blocks: Vec<HashMap<String, usize>>,
+    slot_count: usize,
+    // the frame of one iteration of a for loop, not a function of its own
+    iteration: bool,
+}
+
+struct Resolver {
+    functions: Vec<FunctionScope>,
+    globals: HashSet<String>,
+    // the labels of the loops around the current expression, None for unlabeled ones
+    loops: Vec<Option<String>>,
+    unknown_labels: Vec<Spanned<String>>,
+}
+
+// returns how many slots the script frame needs, or the break 'label and
+// continue 'label expressions whose label isnt on a loop around them
+pub fn resolve(ast: &mut [SpannedExpr], globals: HashSet<String>) -> Result<usize, Vec<Spanned<String>>> {
+    let mut resolver = Resolver {
+        functions: vec![FunctionScope { blocks: vec![], slot_count: 0, iteration: false }],
+        globals,
+        loops: vec![],
+        unknown_labels: vec![],
+    };
+
+    for expr in ast {
+        resolver.resolve(expr);
+    }
+
+    match resolver.unknown_labels.is_empty() {
+        true => Ok(resolver.functions[0].slot_count),
+        false => Err(resolver.unknown_labels),
+    }
+}
+
+impl Resolver {
+    fn current(&mut self) -> &mut FunctionScope {
+        self.functions.last_mut().unwrap()
+    }
+
+    fn lookup(&self, name: &str) -> Option<Slot> {
+        for (depth, function) in self.functions.iter().rev().enumerate() {
+            for block in function.blocks.iter().rev() {
+                if let Some(index) = block.get(name) {
+                    return Some(Slot { depth, index: *index });
+                }
+            }
+        }
+
+        None
+    }
+
+    fn declare(&mut self, name: &str) -> Slot {
+        let function = self.current();
+        let index = function.slot_count;
+        function.slot_count += 1;
+
+        function.blocks.last_mut().unwrap().insert(name.to_string(), index);
+
+        Slot { depth: 0, index }
+    }
+
+    // where a let or fn statement stores its value, None for a global
+    // a name that already exists in the current function is reused, like let i = i + 1;
+    fn bind(&mut self, name: &str) -> Option<Slot> {
+        // the frames of the loops around the name belong to the same function
+        let iterations = self.functions.iter().rev().take_while(|function| function.iteration).count();
+        let at_script_level = self.functions.len() == iterations + 1;
+
+        if at_script_level && self.current().blocks.is_empty() {
+            self.globals.insert(name.to_string());
+            return None;
+        }
+
+        if let Some(slot) = self.lookup(name) && slot.depth <= iterations {
+            return Some(slot);
+        }
+
+        if at_script_level && self.globals.contains(name) {
+            return None;
+        }
+
+        Some(self.declare(name))
+    }
+
+    fn resolve_function(&mut self, args: &[String], body: &mut Rc<SpannedExpr>) -> usize {
+        self.functions.push(FunctionScope { blocks: vec![HashMap::new()], slot_count: 0, iteration: false });
+
+        for arg in args {
+            self.declare(arg);
+        }
+
+        // a break in a function cant leave the loops around the function
+        let loops = std::mem::take(&mut self.loops);
+        self.resolve(Rc::make_mut(body));
+        self.loops = loops;
+
+        self.functions.pop().unwrap().slot_count
+    }
+
+    fn resolve_loop(&mut self, label: &Option<String>, body: &mut SpannedExpr) {
+        self.loops.push(label.clone());
+        self.resolve(body);
+        self.loops.pop();
+    }
+
+    fn check_label(&mut self, label: &Option<String>, span: Span) {
+        if let Some(label) = label && !self.loops.contains(&Some(label.clone())) {
+            self.unknown_labels.push(Spanned { node: label.clone(), span });
+        }
+    }
+
+    // the names of a match arm are always new variables of the arm, so a failed
+    // match cant overwrite a variable outside, the alternatives of a | share theirs
+    fn bind_arm(&mut self, name: &str) -> Option<Slot> {
+        if let Some(index) = self.current().blocks.last().unwrap().get(name) {
+            return Some(Slot { depth: 0, index: *index });
+        }
+
+        Some(self.declare(name))
+    }
+
+    fn resolve_pattern(&mut self, pattern: &mut Spanned<Pattern>, in_arm: bool) {
+        match &mut pattern.node {
+            Pattern::Binding { name, slot } if in_arm => *slot = self.bind_arm(name),
+            Pattern::Binding { name, slot } => *slot = self.bind(name),
+
+            Pattern::Array { elements, rest } => {
+                for element in elements {
+                    self.resolve_pattern(element, in_arm);
+                }
+
+                if let Some(rest) = rest {
+                    self.resolve_pattern(rest, in_arm);
+                }
+            }
+
+            Pattern::Object { properties } => {
+                for (_, property) in properties {
+                    self.resolve_pattern(property, in_arm);
+                }
+            }
+
+            Pattern::Or(alternatives) => {
+                for alternative in alternatives {
+                    self.resolve_pattern(alternative, in_arm);
+                }
+            }
+
+            Pattern::Variant { values, .. } => {
+                for value in values.iter_mut().flatten() {
+                    self.resolve_pattern(value, in_arm);
+                }
+            }
+
+            Pattern::Wildcard | Pattern::Literal(_) | Pattern::Range { .. } => {}
+        }
+    }
+
+    fn resolve_all(&mut self, exprs: &mut [SpannedExpr]) {
+        for expr in exprs {
+            self.resolve(expr);
+        }
+    }
+
+    fn resolve(&mut self, expr: &mut SpannedExpr) {
+        match &mut expr.node {
+            Expr::Identifier(name) => {
+                if let Some(slot) = self.lookup(name) {
+                    expr.node = Expr::Local { slot };
+                }
+            }
+
+            Expr::Let { name, value, slot, .. } => {
+                // lets the function refer to itself, let fact = fn(n) { ... fact(n - 1) };
+                if matches!(value.node, Expr::Lambda { .. }) {
+                    *slot = self.bind(name);
+                    self.resolve(value);
+                } else {
+                    self.resolve(value);
+                    *slot = self.bind(name);
+                }
+            }
+
+            Expr::Destructure { pattern, value } => {
+                self.resolve(value);
+                self.resolve_pattern(pattern, false);
+            }
+
+            Expr::Match { value, arms } => {
+                self.resolve(value);
+
+                for arm in arms {
+                    self.current().blocks.push(HashMap::new());
+                    self.resolve_pattern(&mut arm.pattern, true);
+
+                    if let Some(guard) = &mut arm.guard {
+                        self.resolve(guard);
+                    }
+
+                    self.resolve(&mut arm.body);
+                    self.current().blocks.pop();
+                }
+            }
+
+            Expr::Function { name, args, body, slot, frame_size, .. } => {
+                *slot = self.bind(name);
+                *frame_size = self.resolve_function(args, body);
+            }
+
+            Expr::Struct { name, methods, slot, .. } => {
+                *slot = self.bind(name);
+
+                for method in methods {
+                    method.frame_size = self.resolve_function(&method.args, &mut method.body);
+                }
+            }
+
+            Expr::Enum { name, slot, .. } => *slot = self.bind(name),
+
+            Expr::Lambda { args, body, frame_size, .. } => {
+                *frame_size = self.resolve_function(args, body);
+            }
+
+            Expr::Block { statements, value } => {
+                self.current().blocks.push(HashMap::new());
+
+                // functions, structs and enums of a block are known before any of their
+                // bodies, so two functions in a block can call each other
+                for statement in statements.iter_mut() {
+                    match &mut statement.node {
+                        Expr::Function { name, slot, .. }
+                        | Expr::Struct { name, slot, .. }
+                        | Expr::Enum { name, slot, .. } => *slot = self.bind(name),
+                        _ => {}
+                    }
+                }
+
+                self.resolve_all(statements);
+
+                if let Some(value) = value {
+                    self.resolve(value);
+                }
+
+                self.current().blocks.pop();
+            }
+
+            Expr::ForLoop { iterator_name, index_name, iterator_slot, index_slot, iterator_range, body, label, scope_size } => {
+                self.resolve(iterator_range);
+
+                // only loops that make closures pay for a frame per iteration
+                let scoped = makes_closure(body);
+
+                match scoped {
+                    true => self.functions.push(FunctionScope { blocks: vec![HashMap::new()], slot_count: 0, iteration: true }),
+                    false => self.current().blocks.push(HashMap::new()),
+                }
+
+                if let Some(index_name) = index_name {
+                    *index_slot = Some(self.declare(index_name));
+                }
+
+                *iterator_slot = Some(self.declare(iterator_name));
+                self.resolve_loop(label, body);
+
+                match scoped {
+                    true => *scope_size = Some(self.functions.pop().unwrap().slot_count),
+                    false => { self.current().blocks.pop(); }
+                }
+            }
+
+            Expr::Try { body, catch_name, catch_slot, catch_body, finally_body } => {
+                self.resolve(body);
+
+                if let Some(catch_body) = catch_body {
+                    self.current().blocks.push(HashMap::new());
+
+                    if let Some(catch_name) = catch_name {
+                        *catch_slot = Some(self.declare(catch_name));
+                    }
+
+                    self.resolve(catch_body);
+                    self.current().blocks.pop();
+                }
+
+                if let Some(finally_body) = finally_body {
+                    self.resolve(finally_body);
+                }
+            }
+
+            Expr::Import { name, import_as } => {
+                match import_as.as_deref() {
+                    Some("*") => {}
+                    Some(as_name) => { self.globals.insert(as_name.to_string()); }
+                    None => { self.globals.insert(name.replace(".modu", "")); }
+                }
+            }
+
+            Expr::Call { callee, args, named } => {
+                self.resolve(callee);
+                self.resolve_all(args);
+
+                for (_, arg) in named {
+                    self.resolve(arg);
+                }
+            }
+
+            Expr::Array(elements) | Expr::Interpolation(elements) => self.resolve_all(elements),
+
+            Expr::ObjectLiteral { properties } => {
+                for (key, value) in properties {
+                    self.resolve(key);
+                    self.resolve(value);
+                }
+            }
+
+            Expr::If { condition, then_branch, else_branch } => {
+                self.resolve(condition);
+                self.resolve(then_branch);
+
+                if let Some(else_branch) = else_branch {
+                    self.resolve(else_branch);
+                }
+            }
+
+            Expr::WhileLoop { condition, body, label } => {
+                self.resolve(condition);
+                self.resolve_loop(label, body);
+            }
+
+            Expr::InfiniteLoop { body, label } => self.resolve_loop(label, body),
+
+            Expr::Break(label) | Expr::Continue(label) => self.check_label(label, expr.span),
+
+            Expr::Assign { target: left, value: right }
+            | Expr::CompoundAssign { target: left, value: right, .. }
+            | Expr::IndexAccess { object: left, index: right }
+            | Expr::Range { start: left, end: right }
+            | Expr::InclusiveRange { start: left, end: right }
+            | Expr::Add(left, right)
+            | Expr::Sub(left, right)
+            | Expr::Mul(left, right)
+            | Expr::Div(left, right)
+            | Expr::Mod(left, right)
+            | Expr::Equal(left, right)
+            | Expr::NotEqual(left, right)
+            | Expr::LessThan(left, right)
+            | Expr::LessThanOrEqual(left, right)
+            | Expr::GreaterThan(left, right)
+            | Expr::GreaterThanOrEqual(left, right)
+            | Expr::And(left, right)
+            | Expr::Or(left, right) => {
+                self.resolve(left);
+                self.resolve(right);
+            }
+
+            Expr::Return(inner)
+            | Expr::Throw(inner)
+            | Expr::Neg(inner)
+            | Expr::Not(inner)
+            | Expr::Default { value: inner, .. }
+            | Expr::PropertyAccess { object: inner, .. } => self.resolve(inner),
+
+            Expr::Int(_)
+            | Expr::Float(_)
+            | Expr::String(_)
+            | Expr::Bool(_)
+            | Expr::Null
+            | Expr::Local { .. } => {}
+        }
+    }
+}
+
+// whether running the expression can make a function, which would capture the frame it runs in
+fn makes_closure(expr: &SpannedExpr) -> bool {
+    let any = |exprs: &[SpannedExpr]| exprs.iter().any(makes_closure);
+
+    match &expr.node {
+        Expr::Function { .. } | Expr::Lambda { .. } | Expr::Struct { .. } => true,
+
+        Expr::Block { statements, value } => any(statements) || value.as_deref().is_some_and(makes_closure),
+        Expr::Array(elements) | Expr::Interpolation(elements) => any(elements),
+        Expr::Call { callee, args, named } => makes_closure(callee) || any(args) || named.iter().any(|(_, arg)| makes_closure(arg)),
+        Expr::ObjectLiteral { properties } => properties.iter().any(|(key, value)| makes_closure(key) || makes_closure(value)),
+
+        Expr::If { condition, then_branch, else_branch } => {
+            makes_closure(condition) || makes_closure(then_branch) || else_branch.as_deref().is_some_and(makes_closure)
+        }
+
+        Expr::Match { value, arms } => {
+            makes_closure(value) || arms.iter().any(|arm| arm.guard.as_ref().is_some_and(makes_closure) || makes_closure(&arm.body))
+        }
+
+        Expr::Try { body, catch_body, finally_body, .. } => {
+            makes_closure(body) || catch_body.as_deref().is_some_and(makes_closure) || finally_body.as_deref().is_some_and(makes_closure)
+        }
+
+        Expr::ForLoop { iterator_range: left, body: right, .. }
+        | Expr::WhileLoop { condition: left, body: right, .. }
+        | Expr::Assign { target: left, value: right }
+        | Expr::CompoundAssign { target: left, value: right, .. }
+        | Expr::IndexAccess { object: left, index: right }
+        | Expr::Range { start: left, end: right }
+        | Expr::InclusiveRange { start: left, end: right }
+        | Expr::Add(left, right)
+        | Expr::Sub(left, right)
+        | Expr::Mul(left, right)
+        | Expr::Div(left, right)
+        | Expr::Mod(left, right)
+        | Expr::Equal(left, right)
+        | Expr::NotEqual(left, right)
+        | Expr::LessThan(left, right)
+        | Expr::LessThanOrEqual(left, right)
+        | Expr::GreaterThan(left, right)
+        | Expr::GreaterThanOrEqual(left, right)
+        | Expr::And(left, right)
+        | Expr::Or(left, right) => makes_closure(left) || makes_closure(right),
+
+        Expr::Let { value: inner, .. }
+        | Expr::Destructure { value: inner, .. }
+        | Expr::InfiniteLoop { body: inner, .. }
+        | Expr::Return(inner)
+        | Expr::Throw(inner)
+        | Expr::Neg(inner)
+        | Expr::Not(inner)
+        | Expr::Default { value: inner, .. }
+        | Expr::PropertyAccess { object: inner, .. } => makes_closure(inner),
+
+        Expr::Int(_)
+        | Expr::Float(_)
+        | Expr::String(_)
+        | Expr::Identifier(_)
+        | Expr::Bool(_)
+        | Expr::Null
+        | Expr::Local { .. }
+        | Expr::Enum { .. }
+        | Expr::Import { .. }
+        | Expr::Break(_)
+        | Expr::Continue(_) => false,
+    }
+}
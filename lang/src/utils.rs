@@ -1,9 +1,10 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::rc::Rc;
 
-pub fn create_context() -> HashMap<String, crate::ast::Expr> {
-    let mut context = HashMap::new();
+pub fn create_context() -> crate::env::Globals {
+    let mut context = rustc_hash::FxHashMap::default();
 
     crate::builtins::misc::fill_context(&mut context);
 
-    return context;
+    return Rc::new(RefCell::new(context));
 }
\ No newline at end of file
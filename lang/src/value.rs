@@ -0,0 +1,305 @@
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+#[cfg(not(target_arch = "wasm32"))]
+use libloading::Library;
+use std::sync::Arc;
+
+use crate::ast::{Arity, Spanned, SpannedExpr};
+use crate::compiler::Chunk;
+use crate::env::Env;
+use crate::lexer::Span;
+
+// what expressions evaluate to, the ast itself only describes the source
+// strings, arrays and objects are reference counted so copying values around is cheap,
+// changing one (arr.push, obj.x = 1) copies it first if something else still holds it
+#[derive(Debug, Clone)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    String(Rc<str>),
+    Bool(bool),
+    Null,
+
+    Array(Rc<Vec<Value>>),
+    Object(Rc<HashMap<String, Value>>),
+
+    // start..end and start..=end, bounds are evaluated when the range is created
+    Range(i64, i64),
+    InclusiveRange(i64, i64),
+
+    Function(Rc<Function>),
+    InternalFunction(Rc<InternalFunction>),
+
+    // struct Point { x, y }, calling it makes an Instance
+    Struct(Rc<Struct>),
+    // like objects, changing a field copies the fields if the instance is shared
+    Instance(Rc<Instance>),
+
+    // enum Shape { Circle(r), Empty }, Shape.Empty is a Variant already,
+    // Shape.Circle is a VariantConstructor that makes one when called
+    Enum(Rc<Enum>),
+    Variant(Rc<Variant>),
+    VariantConstructor(Rc<Enum>, usize),
+
+    Module(Rc<HashMap<String, Value>>),
+
+    #[cfg(not(target_arch = "wasm32"))]
+    FFILibrary(Arc<Library>),
+
+    Iterator(ValueIterator),
+}
+
+pub struct Function {
+    pub name: String,
+    pub args: Vec<String>,
+    pub arity: Arity,
+    pub body: Rc<SpannedExpr>,
+    pub frame_size: usize,
+    // the environment the function was defined in
+    pub captured: Env,
+    // the body compiled for the vm, filled in the first time the vm calls it
+    pub code: OnceCell<Rc<Chunk>>,
+}
+
+impl Function {
+    pub fn new(name: String, args: Vec<String>, arity: Arity, body: Rc<SpannedExpr>, frame_size: usize, captured: Env) -> Self {
+        Function { name, args, arity, body, frame_size, captured, code: OnceCell::new() }
+    }
+
+    pub fn takes_self(&self) -> bool {
+        self.args.first().is_some_and(|arg| arg == "self")
+    }
+}
+
+impl std::fmt::Debug for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Function({})", self.name)
+    }
+}
+
+pub struct Struct {
+    pub name: String,
+    pub fields: Vec<String>,
+    // methods take self as their first argument, the others are called on the struct itself
+    pub methods: HashMap<String, Rc<Function>>,
+}
+
+impl Struct {
+    pub fn field(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|field| field == name)
+    }
+
+    // init(self, ...) replaces the default constructor that takes every field in order
+    pub fn init(&self) -> Option<&Rc<Function>> {
+        self.methods.get("init").filter(|init| init.takes_self())
+    }
+}
+
+impl std::fmt::Debug for Struct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Struct({})", self.name)
+    }
+}
+
+// the fields are in the order the struct declares them
+#[derive(Debug, Clone)]
+pub struct Instance {
+    pub of: Rc<Struct>,
+    pub fields: Vec<Value>,
+}
+
+pub struct Enum {
+    pub name: String,
+    // every variant with the names of its values, empty for ones like Empty
+    pub variants: Vec<(String, Vec<String>)>,
+}
+
+impl Enum {
+    pub fn variant(&self, name: &str) -> Option<usize> {
+        self.variants.iter().position(|(variant, _)| variant == name)
+    }
+}
+
+impl std::fmt::Debug for Enum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Enum({})", self.name)
+    }
+}
+
+#[derive(Clone)]
+pub struct Variant {
+    pub of: Rc<Enum>,
+    pub index: usize,
+    pub values: Vec<Value>,
+}
+
+impl Variant {
+    pub fn name(&self) -> &str {
+        &self.of.variants[self.index].0
+    }
+}
+
+impl std::fmt::Debug for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.of.name, self.name())?;
+
+        if !self.values.is_empty() {
+            write!(f, "({:?})", self.values)?;
+        }
+
+        Ok(())
+    }
+}
+
+// the rust side of an InternalFunction, spans point at the arguments for error reports
+pub type NativeFn = fn(Vec<Spanned<Value>>) -> Result<InternalFunctionResponse, (String, Span)>;
+
+pub struct InternalFunction {
+    pub name: String,
+    pub args: Vec<String>, // Vec<"__args__"> for an optional amount
+    pub func: NativeFn,
+}
+
+impl std::fmt::Debug for InternalFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "InternalFunction({})", self.name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InternalFunctionResponse {
+    pub return_value: Value,
+    pub replace_self: Option<Value>,
+}
+
+// values that are produced lazily while a for loop runs, like os.read_lines()
+// clones share the same underlying iterator, so it can only be consumed once
+#[derive(Clone)]
+pub struct ValueIterator(pub Rc<RefCell<dyn Iterator<Item = Result<Value, String>>>>);
+
+impl ValueIterator {
+    pub fn new(iter: impl Iterator<Item = Result<Value, String>> + 'static) -> Self {
+        ValueIterator(Rc::new(RefCell::new(iter)))
+    }
+}
+
+impl std::fmt::Debug for ValueIterator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Iterator")
+    }
+}
+
+impl Value {
+    pub fn internal_function(
+        name: &str,
+        args: &[&str],
+        func: NativeFn,
+    ) -> Value {
+        Value::InternalFunction(Rc::new(InternalFunction {
+            name: name.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            func,
+        }))
+    }
+
+    // strings inside arrays and objects are quoted, on their own they are not
+    fn fmt_nested(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "\"{}\"", s),
+            v => write!(f, "{}", v),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(fl) => write!(f, "{}", fl),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Null => write!(f, "null"),
+            Value::Range(start, end) => write!(f, "{}..{}", start, end),
+            Value::InclusiveRange(start, end) => write!(f, "{}..={}", start, end),
+            Value::Function(function) => write!(f, "<function {}>", function.name),
+            Value::InternalFunction(function) => write!(f, "<function {}>", function.name),
+            Value::Struct(definition) => write!(f, "<struct {}>", definition.name),
+            Value::Enum(definition) => write!(f, "<enum {}>", definition.name),
+            Value::VariantConstructor(definition, index) => write!(f, "<function {}.{}>", definition.name, definition.variants[*index].0),
+            Value::Module(_) => write!(f, "<module>"),
+            #[cfg(not(target_arch = "wasm32"))]
+            Value::FFILibrary(_) => write!(f, "<ffi library>"),
+            Value::Iterator(_) => write!(f, "<iterator>"),
+
+            Value::Array(elements) => {
+                write!(f, "[")?;
+
+                for (i, element) in elements.iter().enumerate() {
+                    element.fmt_nested(f)?;
+
+                    if i != elements.len() - 1 {
+                        write!(f, ", ")?;
+                    }
+                }
+
+                write!(f, "]")
+            }
+
+            Value::Instance(instance) => {
+                write!(f, "{} {{ ", instance.of.name)?;
+
+                for (i, (name, value)) in instance.of.fields.iter().zip(instance.fields.iter()).enumerate() {
+                    write!(f, "{}: ", name)?;
+                    value.fmt_nested(f)?;
+
+                    if i != instance.fields.len() - 1 {
+                        write!(f, ", ")?;
+                    }
+                }
+
+                write!(f, " }}")
+            }
+
+            // Shape.Circle(2), or just Shape.Empty without values
+            Value::Variant(variant) => {
+                write!(f, "{}.{}", variant.of.name, variant.name())?;
+
+                if variant.values.is_empty() {
+                    return Ok(());
+                }
+
+                write!(f, "(")?;
+
+                for (i, value) in variant.values.iter().enumerate() {
+                    value.fmt_nested(f)?;
+
+                    if i != variant.values.len() - 1 {
+                        write!(f, ", ")?;
+                    }
+                }
+
+                write!(f, ")")
+            }
+
+            // keys are sorted so the output doesnt change between runs
+            Value::Object(properties) => {
+                let mut keys = properties.keys().collect::<Vec<&String>>();
+                keys.sort();
+
+                write!(f, "{{ ")?;
+
+                for (i, key) in keys.iter().enumerate() {
+                    write!(f, "{}: ", key)?;
+                    properties[*key].fmt_nested(f)?;
+
+                    if i != keys.len() - 1 {
+                        write!(f, ", ")?;
+                    }
+                }
+
+                write!(f, " }}")
+            }
+        }
+    }
+}
@@ -0,0 +1,642 @@
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ast::{Spanned, SpannedExpr};
+use crate::compiler::{self, Chunk, Op, Prototype};
+use crate::env::{Env, Frame, Slot, Source};
+use crate::eval::{self, EvalError, Flow, NamedArgs, TraceFrame};
+use crate::lexer::Span;
+use crate::value::{Function, Value, ValueIterator};
+
+// runs the bytecode from compiler.rs, used instead of eval::eval with modu run --vm
+//
+// calls to modu functions push a frame instead of recursing in rust, so deep
+// recursion only grows the frames vec. natives and imports are shared with eval
+
+struct CallFrame {
+    chunk: Rc<Chunk>,
+    ip: usize,
+    env: Env,
+    // where the stack of this call starts
+    base: usize,
+    // the span of the call, for errors that happen because of the call itself
+    span: Span,
+    write_back: bool,
+    kind: FrameKind,
+    // None for the statement the vm was started with
+    function: Option<Rc<Function>>,
+    // the file span is in, None if it is the file of the caller, which it is unless this was a tail call
+    call_source: Option<Rc<Source>>,
+    // the call this frame was first made for, before tail calls replaced it, so traces still show it
+    replaced: Option<TraceFrame>,
+}
+
+// methods and init hand back what self is in slot 0 when they return, so
+// their frames are never replaced by a tail call
+#[derive(Clone, Copy, PartialEq)]
+enum FrameKind {
+    Function,
+    Method,
+    Constructor,
+}
+
+// installed by Try and TryFinally, remembers how to get back to the try statement
+struct Handler {
+    frames: usize,
+    // the frame of the for loop iteration the try is in, when there is one
+    scope: Rc<Frame>,
+    stack: usize,
+    pending: usize,
+    target: usize,
+    catches: bool,
+}
+
+struct Vm {
+    stack: Vec<Value>,
+    frames: Vec<CallFrame>,
+    frame: CallFrame,
+    handlers: Vec<Handler>,
+    // errors waiting for their finally block to finish, see Op::TryFinally
+    pending: Vec<EvalError>,
+}
+
+// same contract as eval::eval, parse calls this for every top level statement
+pub fn eval(expr: &SpannedExpr, env: &Env) -> Result<Flow, EvalError> {
+    let chunk = Rc::new(compiler::compile(expr)?);
+
+    run(chunk, env.clone())
+}
+
+fn unexpected(is_break: bool, span: Span) -> EvalError {
+    if is_break {
+        EvalError {
+            message: "Unexpected break in function".to_string(),
+            message_short: "unexpected break".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }
+    } else {
+        EvalError {
+            message: "Unexpected skip in function".to_string(),
+            message_short: "unexpected skip".to_string(),
+            span,
+            source: None,
+            trace: vec![],
+            thrown: None,
+        }
+    }
+}
+
+fn spanned_args(args: impl Iterator<Item = Value>, spans: &[Span]) -> Vec<Spanned<Value>> {
+    args.zip(spans)
+        .map(|(node, span)| Spanned { node, span: *span })
+        .collect()
+}
+
+// the named args of a call are the last ones, see compiler::CallInfo
+fn split_named(mut args: Vec<Spanned<Value>>, names: &[Spanned<String>]) -> (Vec<Spanned<Value>>, NamedArgs) {
+    let named = args.split_off(args.len() - names.len());
+    (args, names.iter().cloned().zip(named).collect())
+}
+
+fn run(chunk: Rc<Chunk>, env: Env) -> Result<Flow, EvalError> {
+    let mut vm = Vm {
+        stack: Vec::new(),
+        frames: Vec::new(),
+        frame: CallFrame {
+            chunk,
+            ip: 0,
+            env,
+            base: 0,
+            span: Span::from(0..0),
+            write_back: false,
+            kind: FrameKind::Function,
+            function: None,
+            call_source: None,
+            replaced: None,
+        },
+        handlers: Vec::new(),
+        pending: Vec::new(),
+    };
+
+    loop {
+        let mut error = match vm.execute() {
+            Ok(flow) => return Ok(flow),
+            Err(error) => error,
+        };
+
+        error.source.get_or_insert_with(|| vm.frame.env.source.clone());
+
+        let handler = vm.handlers.pop();
+
+        // leave the calls between the error and the try statement, or all of them
+        // if nothing catches it, adding each to the trace on the way
+        while vm.frames.len() > handler.as_ref().map_or(0, |handler| handler.frames) {
+            let caller = vm.frames.pop().unwrap();
+            let callee = std::mem::replace(&mut vm.frame, caller);
+
+            if let Some(function) = callee.function {
+                error.trace.push(TraceFrame {
+                    function: function.name.clone(),
+                    span: callee.span,
+                    source: callee.call_source.unwrap_or_else(|| vm.frame.env.source.clone()),
+                });
+            }
+
+            error.trace.extend(callee.replaced);
+        }
+
+        let Some(handler) = handler else {
+            return Err(error);
+        };
+
+        vm.stack.truncate(handler.stack);
+        vm.pending.truncate(handler.pending);
+        vm.frame.ip = handler.target;
+        vm.frame.env.frame = handler.scope;
+
+        if handler.catches {
+            vm.stack.push(eval::error_value(&error, &vm.frame.env.source));
+        } else {
+            vm.pending.push(error);
+        }
+    }
+}
+
+impl Vm {
+    // functions made by the vm capture the frame they are made in
+    fn instantiate(&self, prototype: &Prototype) -> Function {
+        Function {
+            name: prototype.name.clone(),
+            args: prototype.args.clone(),
+            arity: prototype.arity,
+            body: prototype.body.clone(),
+            frame_size: prototype.frame_size,
+            captured: self.frame.env.clone(),
+            code: OnceCell::from(prototype.code.clone()),
+        }
+    }
+
+    fn handler(&mut self, target: usize, catches: bool) {
+        self.handlers.push(Handler {
+            frames: self.frames.len(),
+            scope: self.frame.env.frame.clone(),
+            stack: self.stack.len(),
+            pending: self.pending.len(),
+            target,
+            catches,
+        });
+    }
+
+    fn execute(&mut self) -> Result<Flow, EvalError> {
+        loop {
+            let ip = self.frame.ip;
+            self.frame.ip += 1;
+
+            let span = self.frame.chunk.spans[ip];
+
+            match &self.frame.chunk.code[ip] {
+                Op::Push(value) => self.stack.push(value.clone()),
+
+                Op::Pop => {
+                    self.stack.pop();
+                }
+
+                Op::PopN(n) => {
+                    self.stack.truncate(self.stack.len() - n);
+                }
+
+                Op::Dup => self.stack.push(self.stack.last().unwrap().clone()),
+
+                Op::GetLocal(slot) => self.stack.push(self.frame.env.get(*slot)),
+
+                Op::SetLocal(slot) => self.frame.env.set(*slot, self.stack.pop().unwrap()),
+
+                Op::GetGlobal(name) => {
+                    match self.frame.env.get_global(name) {
+                        Some(value) => self.stack.push(value),
+                        None => return Err(EvalError {
+                            message: format!("Undefined variable: {}", name),
+                            message_short: "not defined".to_string(),
+                            span,
+                            source: None,
+                            trace: vec![],
+                            thrown: None,
+                        }),
+                    }
+                }
+
+                Op::DefineGlobal(name) => self.frame.env.set_global(name, self.stack.pop().unwrap()),
+
+                Op::SetGlobal(name) => {
+                    if !self.frame.env.has_global(name) {
+                        return Err(EvalError {
+                            message: format!("Cannot assign to undefined variable: {}", name),
+                            message_short: "not defined".to_string(),
+                            span,
+                            source: None,
+                            trace: vec![],
+                            thrown: None,
+                        });
+                    }
+
+                    self.frame.env.set_global(name, self.stack.pop().unwrap());
+                }
+
+                Op::GetProperty(property) => {
+                    let object = self.stack.pop().unwrap();
+                    self.stack.push(eval::get_property(object, property, span)?);
+                }
+
+                Op::GetIndex => {
+                    let index = self.stack.pop().unwrap();
+                    let object = self.stack.pop().unwrap();
+                    self.stack.push(eval::get_index(object, index, span)?);
+                }
+
+                Op::GetTarget(target, count) => {
+                    let indexes = &self.stack[self.stack.len() - count..];
+                    let value = eval::read_target(target, indexes, &self.frame.env)?;
+                    self.stack.push(value);
+                }
+
+                Op::SetTarget(target, count) => {
+                    let value = self.stack.pop().unwrap();
+                    let indexes = self.stack.split_off(self.stack.len() - count);
+                    eval::write_target(target, &indexes, value, &self.frame.env)?;
+                }
+
+                Op::SetProperty(property) => {
+                    let container = self.stack.pop().unwrap();
+                    let value = self.stack.pop().unwrap();
+                    self.stack.push(eval::set_property(container, property, value, span)?);
+                }
+
+                Op::SetIndex(index_span) => {
+                    let index = self.stack.pop().unwrap();
+                    let container = self.stack.pop().unwrap();
+                    let value = self.stack.pop().unwrap();
+                    self.stack.push(eval::set_index(container, index, value, span, *index_span)?);
+                }
+
+                Op::UnpackArray { count, rest } => {
+                    let value = self.stack.pop().unwrap();
+                    let values = eval::unpack_array(value, *count, *rest, span)?;
+
+                    self.stack.extend(values.into_iter().rev());
+                }
+
+                Op::UnpackObject(keys) => {
+                    let value = self.stack.pop().unwrap();
+                    let values = eval::unpack_object(value, keys.iter(), span)?;
+
+                    self.stack.extend(values.into_iter().rev());
+                }
+
+                Op::Destructure(pattern) => {
+                    let value = self.stack.pop().unwrap();
+                    eval::destructure(pattern, value, &self.frame.env)?;
+                }
+
+                Op::Match(pattern) => {
+                    let value = self.stack.pop().unwrap();
+                    let matched = eval::match_pattern(pattern, &value, &self.frame.env)?;
+
+                    self.stack.push(Value::Bool(matched));
+                }
+
+                Op::NoMatch => return Err(eval::no_match(self.stack.last().unwrap(), span)),
+
+                Op::MakeArray(n) => {
+                    let elements = self.stack.split_off(self.stack.len() - n);
+                    self.stack.push(Value::Array(Rc::new(elements)));
+                }
+
+                Op::Interpolate(n) => {
+                    let parts = self.stack.split_off(self.stack.len() - n);
+                    self.stack.push(eval::interpolate(parts));
+                }
+
+                Op::MakeObject(key_spans) => {
+                    let mut properties = HashMap::new();
+                    let pairs = self.stack.split_off(self.stack.len() - key_spans.len() * 2);
+
+                    for (pair, key_span) in pairs.chunks(2).zip(key_spans.iter()) {
+                        let key = eval::object_key(pair[0].clone(), *key_span)?;
+                        properties.insert(key, pair[1].clone());
+                    }
+
+                    self.stack.push(Value::Object(Rc::new(properties)));
+                }
+
+                Op::MakeRange { inclusive } => {
+                    let end = eval::range_bound(self.stack.pop().unwrap(), "end", span)?;
+                    let start = eval::range_bound(self.stack.pop().unwrap(), "start", span)?;
+
+                    self.stack.push(if *inclusive {
+                        Value::InclusiveRange(start, end)
+                    } else {
+                        Value::Range(start, end)
+                    });
+                }
+
+                Op::MakeFunction(prototype) => {
+                    let function = self.instantiate(prototype);
+                    self.stack.push(Value::Function(Rc::new(function)));
+                }
+
+                Op::MakeStruct(prototype) => {
+                    let methods = prototype.methods.iter().map(|(name, method)| (name.clone(), self.instantiate(method)));
+                    self.stack.push(eval::make_struct(&prototype.name, &prototype.fields, methods));
+                }
+
+                Op::Binary(op, right_span) => {
+                    let right = self.stack.pop().unwrap();
+                    let left = self.stack.pop().unwrap();
+                    self.stack.push(eval::binary(*op, left, right, span, *right_span)?);
+                }
+
+                Op::Neg => {
+                    let value = self.stack.pop().unwrap();
+                    self.stack.push(eval::negate(value, span)?);
+                }
+
+                Op::Not => {
+                    let value = self.stack.pop().unwrap();
+                    self.stack.push(Value::Bool(!eval::condition(value, span)?));
+                }
+
+                Op::Condition => {
+                    let value = self.stack.pop().unwrap();
+                    self.stack.push(Value::Bool(eval::condition(value, span)?));
+                }
+
+                Op::JumpIfFalse(target) => {
+                    if !eval::condition(self.stack.pop().unwrap(), span)? {
+                        self.frame.ip = *target;
+                    }
+                }
+
+                Op::Jump(target) => self.frame.ip = *target,
+
+                Op::JumpIfPassed(index, target) => {
+                    if self.frame.env.was_passed(*index) {
+                        self.frame.ip = *target;
+                    }
+                }
+
+                Op::Call(info) => {
+                    let info = info.clone();
+                    let argc = info.arg_spans.len();
+                    let function_at = self.stack.len() - argc - 1;
+                    let function = std::mem::replace(&mut self.stack[function_at], Value::Null);
+
+                    let write_back = info.receiver.as_ref().is_some_and(|receiver| receiver.write_back);
+
+                    // obj.method() passes obj as self, it sits right below the function
+                    let method = info.receiver.is_some() && matches!(&function, Value::Function(function) if function.takes_self())
+                        && matches!(self.stack[function_at - 1], Value::Instance(_));
+
+                    // structs with an init run it like a method on a new instance, without one they are native
+                    let callee = match function {
+                        Value::Function(function) if method => Ok((function, Some(self.stack[function_at - 1].clone()), FrameKind::Method)),
+                        Value::Function(function) => Ok((function, None, FrameKind::Function)),
+                        Value::Struct(ref definition) if let Some(init) = definition.init() => {
+                            Ok((init.clone(), Some(eval::new_instance(definition, vec![], span)?), FrameKind::Constructor))
+                        }
+                        native => Err(native),
+                    };
+
+                    match callee {
+                        Ok((function, this, kind)) => {
+                            let skip = this.is_some() as usize;
+                            let arity = match skip {
+                                0 => function.arity,
+                                _ => function.arity.without_self(),
+                            };
+
+                            // named args and wrong counts take the slow way, the args come back in order
+                            let mut skipped = vec![];
+
+                            if !info.named.is_empty() || !arity.accepts(argc) {
+                                let (args, named) = split_named(spanned_args(self.stack.drain(function_at + 1..), &info.arg_spans), &info.named);
+                                let (args, placed_skipped) = eval::function_args(&function, skip, args, named, span)?;
+
+                                self.stack.extend(args.into_iter().map(|arg| arg.node));
+                                skipped = placed_skipped;
+                            }
+
+                            let code = match function.code.get() {
+                                Some(code) => code.clone(),
+                                None => {
+                                    let code = Rc::new(compiler::compile(&function.body).map_err(|mut e| {
+                                        e.source = Some(function.captured.source.clone());
+                                        e
+                                    })?);
+                                    let _ = function.code.set(code.clone());
+                                    code
+                                }
+                            };
+
+                            let call_env = eval::call_env(&function, this.into_iter().chain(self.stack.drain(function_at + 1..)).collect(), skipped);
+
+                            // methods and constructors have to give self back, so only plain calls replace their frame
+                            if info.tail && !self.frames.is_empty() && kind == FrameKind::Function && self.frame.kind == FrameKind::Function {
+                                if self.frame.replaced.is_none() && let Some(returning) = &self.frame.function {
+                                    self.frame.replaced = Some(TraceFrame {
+                                        function: returning.name.clone(),
+                                        span: self.frame.span,
+                                        source: self.frame.call_source.clone().unwrap_or_else(|| self.frames.last().unwrap().env.source.clone()),
+                                    });
+                                }
+
+                                // the call was in the file of the function that is returning, not its caller
+                                self.frame.call_source = Some(self.frame.env.source.clone());
+
+                                self.stack.truncate(self.frame.base);
+                                self.frame.chunk = code;
+                                self.frame.ip = 0;
+                                self.frame.env = call_env;
+                                self.frame.span = span;
+                                self.frame.function = Some(function);
+
+                                continue;
+                            }
+
+                            if self.frames.len() >= eval::max_depth() {
+                                return Err(eval::too_deep(span));
+                            }
+
+                            self.stack.truncate(function_at - info.receiver.is_some() as usize);
+
+                            let callee = CallFrame {
+                                chunk: code,
+                                ip: 0,
+                                env: call_env,
+                                base: self.stack.len(),
+                                span,
+                                write_back,
+                                kind,
+                                function: Some(function),
+                                call_source: None,
+                                replaced: None,
+                            };
+
+                            self.frames.push(std::mem::replace(&mut self.frame, callee));
+                        }
+
+                        Err(native) => {
+                            let (args, named) = split_named(spanned_args(self.stack.drain(function_at + 1..), &info.arg_spans), &info.named);
+                            self.stack.pop();
+
+                            let receiver = info.receiver.as_ref().map(|receiver| (
+                                Spanned { node: self.stack.pop().unwrap(), span: receiver.span },
+                                receiver.name.as_str(),
+                            ));
+
+                            let response = eval::call_native(native, args, named, receiver, span)?;
+                            self.stack.push(response.return_value);
+
+                            if write_back {
+                                match response.replace_self {
+                                    Some(replace_self) => {
+                                        self.stack.push(replace_self);
+                                        self.stack.push(Value::Bool(true));
+                                    }
+
+                                    None => self.stack.push(Value::Bool(false)),
+                                }
+                            }
+                        }
+                    }
+                }
+
+                op @ (Op::Return | Op::End) => {
+                    let is_return = matches!(op, Op::Return);
+                    let value = self.stack.pop().unwrap();
+
+                    let Some(caller) = self.frames.pop() else {
+                        return Ok(if is_return { Flow::Return(value) } else { Flow::Continue(value) });
+                    };
+
+                    self.stack.truncate(self.frame.base);
+                    let write_back = self.frame.write_back;
+                    let kind = self.frame.kind;
+                    let this = match kind {
+                        FrameKind::Function => None,
+                        _ => Some(self.frame.env.get(Slot { depth: 0, index: 0 })),
+                    };
+                    self.frame = caller;
+
+                    match (kind, &this) {
+                        (FrameKind::Constructor, Some(this)) => self.stack.push(this.clone()),
+                        _ => self.stack.push(value),
+                    }
+
+                    // only a method replaces self, with whatever self is when it returns
+                    if write_back {
+                        match (kind, this) {
+                            (FrameKind::Method, Some(this)) => {
+                                self.stack.push(this);
+                                self.stack.push(Value::Bool(true));
+                            }
+
+                            _ => self.stack.push(Value::Bool(false)),
+                        }
+                    }
+                }
+
+                Op::Unwind { is_break, label } => {
+                    // the error is about the call, so it happens in the caller like with eval
+                    if let Some(caller) = self.frames.pop() {
+                        let mut error = unexpected(*is_break, self.frame.span);
+                        error.source = self.frame.call_source.take();
+
+                        self.stack.truncate(self.frame.base);
+                        self.frame = caller;
+
+                        return Err(error);
+                    }
+
+                    return Ok(if *is_break {
+                        Flow::Break(label.clone())
+                    } else {
+                        Flow::Skip(label.clone())
+                    });
+                }
+
+                Op::Iterate => {
+                    let value = self.stack.pop().unwrap();
+                    self.stack.push(Value::Iterator(ValueIterator::new(eval::iterate(value, span)?)));
+                }
+
+                Op::EnterScope(size) => self.frame.env = self.frame.env.call(*size, 0, vec![]),
+                Op::LeaveScope => self.frame.env = self.frame.env.parent(),
+
+                Op::Next(exit) => {
+                    let len = self.stack.len();
+
+                    let next = match &self.stack[len - 2] {
+                        Value::Iterator(iter) => iter.0.borrow_mut().next(),
+                        _ => unreachable!(),
+                    };
+
+                    match next {
+                        Some(Ok(value)) => {
+                            let index = match &mut self.stack[len - 1] {
+                                Value::Int(index) => {
+                                    *index += 1;
+                                    *index - 1
+                                }
+                                _ => unreachable!(),
+                            };
+
+                            self.stack.push(Value::Int(index));
+                            self.stack.push(value);
+                        }
+
+                        Some(Err(msg)) => return Err(EvalError {
+                            message: msg.clone(),
+                            message_short: msg,
+                            span,
+                            source: None,
+                            trace: vec![],
+                            thrown: None,
+                        }),
+
+                        None => self.frame.ip = *exit,
+                    }
+                }
+
+                Op::Import { name, import_as } => {
+                    self.stack.push(eval::import(name, import_as, span, &self.frame.env)?);
+                }
+
+                Op::Throw => return Err(eval::throw(self.stack.pop().unwrap(), span)),
+
+                Op::Try(target) => {
+                    let target = *target;
+                    self.handler(target, true);
+                }
+
+                Op::TryFinally(target) => {
+                    let target = *target;
+                    self.handler(target, false);
+                }
+
+                Op::PopHandler => {
+                    self.handlers.pop();
+                }
+
+                Op::Rethrow => return Err(self.pending.pop().unwrap()),
+
+                Op::Discard => {
+                    self.pending.pop();
+                }
+            }
+        }
+    }
+}
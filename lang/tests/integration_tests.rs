@@ -4,6 +4,15 @@ use std::fs;
 use std::path::Path;
 
 fn run_test(name: &str) {
+    run_test_with_args(name, &[]);
+}
+
+// same as run_test, but with the bytecode vm instead of the tree walker
+fn run_vm_test(name: &str) {
+    run_test_with_args(name, &["--vm"]);
+}
+
+fn run_test_with_args(name: &str, args: &[&str]) {
     let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("cases");
     let modu_file = dir.join(format!("{}.modu", name));
     let expected_file = dir.join(format!("{}.expected", name));
@@ -15,11 +24,61 @@ fn run_test(name: &str) {
         .expect("Failed to find binary")
         .arg("run")
         .arg(modu_file)
+        .args(args)
         .assert()
         .success()
         .stdout(predicate::str::diff(expected_output));
 }
 
+// runs a case that ends in an error, the report has to mention each part of the stack trace
+fn run_trace_test(name: &str, args: &[&str], trace: &[&str]) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("cases");
+    let modu_file = dir.join(format!("{}.modu", name));
+    let expected_file = dir.join(format!("{}.expected", name));
+
+    let expected_output = fs::read_to_string(&expected_file)
+        .expect("Failed to read expected output file");
+
+    let assert = Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .expect("Failed to find binary")
+        .arg("run")
+        .arg(modu_file)
+        .args(args)
+        .assert()
+        .stdout(predicate::str::diff(expected_output));
+
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+    for part in trace {
+        assert!(stderr.contains(part), "{:?} is missing from the report:\n{}", part, stderr);
+    }
+}
+
+// runs modu check on a case, with no errors expected it has to pass,
+// otherwise it has to fail and the report has to mention each error
+fn run_check_test(name: &str, errors: &[&str]) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("cases");
+    let modu_file = dir.join(format!("{}.modu", name));
+
+    let assert = Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .expect("Failed to find binary")
+        .arg("check")
+        .arg(modu_file)
+        .assert();
+
+    if errors.is_empty() {
+        assert.success().stdout(predicate::str::contains("No type errors"));
+        return;
+    }
+
+    let assert = assert.failure();
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+    for error in errors {
+        assert!(stderr.contains(error), "{:?} is missing from the report:\n{}", error, stderr);
+    }
+}
+
 #[test]
 fn basic_print() {
     run_test("basic_print");
@@ -58,4 +117,335 @@ fn crypto() {
 #[test]
 fn minskys_machine() {
     run_test("minskys_machine");
-}
\ No newline at end of file
+}
+
+#[test]
+fn arithmetic() {
+    run_test("arithmetic");
+}
+
+#[test]
+fn integer_overflow() {
+    run_trace_test("integer_overflow", &[], &["Integer overflow: 9223372036854775807 * 2"]);
+}
+
+#[test]
+fn logical() {
+    run_test("logical");
+}
+
+#[test]
+fn assignment() {
+    run_test("assignment");
+}
+
+#[test]
+fn path_assignment() {
+    run_test("path_assignment");
+}
+
+#[test]
+fn object_literals() {
+    run_test("object_literals");
+}
+
+#[test]
+fn while_loops() {
+    run_test("while_loops");
+}
+
+#[test]
+fn iteration() {
+    run_test("iteration");
+}
+
+#[test]
+fn closures() {
+    run_test("closures");
+}
+
+#[test]
+fn scopes() {
+    run_test("scopes");
+}
+
+#[test]
+fn values() {
+    run_test("values");
+}
+
+#[test]
+fn vm_arithmetic() {
+    run_vm_test("arithmetic");
+}
+
+#[test]
+fn vm_path_assignment() {
+    run_vm_test("path_assignment");
+}
+
+#[test]
+fn vm_loops() {
+    run_vm_test("loops");
+}
+
+#[test]
+fn vm_iteration() {
+    run_vm_test("iteration");
+}
+
+#[test]
+fn vm_closures() {
+    run_vm_test("closures");
+}
+
+#[test]
+fn vm_scopes() {
+    run_vm_test("scopes");
+}
+
+#[test]
+fn vm_logical() {
+    run_vm_test("logical");
+}
+
+#[test]
+fn vm_assignment() {
+    run_vm_test("assignment");
+}
+
+#[test]
+fn vm_object_literals() {
+    run_vm_test("object_literals");
+}
+
+#[test]
+fn vm_while_loops() {
+    run_vm_test("while_loops");
+}
+
+#[test]
+fn vm_values() {
+    run_vm_test("values");
+}
+
+#[test]
+fn vm_minskys_machine() {
+    run_vm_test("minskys_machine");
+}
+
+// deeper than the default --max-depth allows
+#[test]
+fn vm_recursion() {
+    run_test_with_args("vm_recursion", &["--vm", "--max-depth", "20000"]);
+}
+
+#[test]
+fn try_catch() {
+    run_test("try_catch");
+}
+
+#[test]
+fn vm_try_catch() {
+    run_vm_test("try_catch");
+}
+
+const STACK_TRACE: &[&str] = &[
+    "Cannot multiply values",
+    "parse_age called at",
+    "stack_trace_module.modu:6:12",
+    "apply called at",
+    "stack_trace.modu:4:15",
+    "load called at",
+    "stack_trace.modu:9:1",
+];
+
+#[test]
+fn stack_trace() {
+    run_trace_test("stack_trace", &[], STACK_TRACE);
+}
+
+#[test]
+fn vm_stack_trace() {
+    run_trace_test("stack_trace", &["--vm"], STACK_TRACE);
+}
+
+// the report points at each break itself, not the loop around it
+const UNKNOWN_LABEL: &[&str] = &["No loop labeled 'nope", "unknown_label.modu:6:9", "No loop labeled 'outer", "unknown_label.modu:13:9"];
+
+#[test]
+fn unknown_label() {
+    run_trace_test("unknown_label", &[], UNKNOWN_LABEL);
+}
+
+#[test]
+fn vm_unknown_label() {
+    run_trace_test("unknown_label", &["--vm"], UNKNOWN_LABEL);
+}
+
+const MAX_DEPTH: &[&str] = &[
+    "Maximum call depth of 50 exceeded",
+    "forever called at",
+    "max_depth.modu:31:12 (49 times)",
+    "max_depth.modu:34:1",
+];
+
+#[test]
+fn max_depth() {
+    run_trace_test("max_depth", &["--max-depth", "50"], MAX_DEPTH);
+}
+
+#[test]
+fn vm_max_depth() {
+    run_trace_test("max_depth", &["--vm", "--max-depth", "50"], MAX_DEPTH);
+}
+
+#[test]
+fn interpolation() {
+    run_test("interpolation");
+}
+
+#[test]
+fn vm_interpolation() {
+    run_vm_test("interpolation");
+}
+
+#[test]
+fn strings() {
+    run_test("strings");
+}
+
+#[test]
+fn vm_strings() {
+    run_vm_test("strings");
+}
+
+#[test]
+fn destructuring() {
+    run_test("destructuring");
+}
+
+#[test]
+fn vm_destructuring() {
+    run_vm_test("destructuring");
+}
+
+#[test]
+fn match_expression() {
+    run_test("match");
+}
+
+#[test]
+fn vm_match_expression() {
+    run_vm_test("match");
+}
+
+#[test]
+fn block_values() {
+    run_test("block_values");
+}
+
+#[test]
+fn vm_block_values() {
+    run_vm_test("block_values");
+}
+
+#[test]
+fn structs() {
+    run_test("structs");
+}
+
+#[test]
+fn vm_structs() {
+    run_vm_test("structs");
+}
+
+#[test]
+fn enums() {
+    run_test("enums");
+}
+
+#[test]
+fn vm_enums() {
+    run_vm_test("enums");
+}
+
+#[test]
+fn types() {
+    run_test("types");
+}
+
+#[test]
+fn vm_types() {
+    run_vm_test("types");
+}
+
+#[test]
+fn check_types() {
+    run_check_test("types", &[]);
+}
+
+#[test]
+fn check_type_errors() {
+    run_check_test("type_errors", &[
+        "Expected string, found int",
+        "Expected int, found string",
+        "Expected int, found float",
+        "Cannot add int and string",
+        "Unknown type Shape",
+        "Expected int, found null",
+        "Missing return, the function has to return int",
+        "Missing return, the function has to return string",
+        "Function scale has no argument named by",
+    ]);
+}
+
+#[test]
+fn check_type_errors_flow() {
+    run_check_test("type_errors_flow", &[
+        "Expected int, found string",
+        "Expected int, found float",
+        "Expected int, found bool",
+        "Function double has no argument named by",
+    ]);
+}
+
+#[test]
+fn default_args() {
+    run_test("default_args");
+}
+
+#[test]
+fn vm_default_args() {
+    run_vm_test("default_args");
+}
+
+#[test]
+fn default_args_order() {
+    run_check_test("default_args_order", &["Argument fill needs a default since an argument before it has one"]);
+}
+
+#[test]
+fn duplicate_args() {
+    run_check_test("duplicate_args", &["Duplicate argument x", "Duplicate argument first"]);
+}
+
+#[test]
+fn named_args() {
+    run_test("named_args");
+}
+
+#[test]
+fn vm_named_args() {
+    run_vm_test("named_args");
+}
+
+#[test]
+fn numeric_literals() {
+    run_test("numeric_literals");
+}
+
+#[test]
+fn vm_numeric_literals() {
+    run_vm_test("numeric_literals");
+}